@@ -1,10 +1,8 @@
 use kan::{data_structures::*, network::Network};
-use rand::{thread_rng, Rng};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::sync::Arc;
 use std::error::Error;
 use serde_json::{to_writer, Error as JsonError};
-use std::fs::{File, OpenOptions};
+use std::fs::OpenOptions;
 
 const LEARNING_RATE: f32 = 0.1;
 const NUM_EPOCHS: u32 = 1000;
@@ -42,7 +40,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         let target = Vector::random(INPUT_SIZE);
 
         // Train the model on the input and target
-        model.train(input, target, LEARNING_RATE);
+        model.train(input, target, LEARNING_RATE)?;
     }
 
     // Evaluate the model on a test set
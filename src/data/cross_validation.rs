@@ -0,0 +1,20 @@
+use crate::data::Dataset;
+use crate::network::{Network, NetworkError};
+
+/// Runs k-fold cross-validation: for each of the `k` folds of `data`, trains a
+/// fresh network from `net_builder` on the other folds for `epochs` epochs
+/// and evaluates it on the held-out fold with `evaluate_batch`.
+///
+/// Returns the `(loss, accuracy)` of every fold, or the first training error.
+/// Panics unless `0 < k <= data.len()`.
+pub fn cross_validate(net_builder: impl Fn() -> Network, data: &Dataset, k: usize, lr: f32, epochs: usize) -> Result<Vec<(f32, f32)>, NetworkError> {
+    assert!(k > 0 && k <= data.len(), "Cannot split {} samples into {} folds", data.len(), k);
+    (0..k)
+        .map(|fold| {
+            let (train, holdout) = data.fold(k, fold);
+            let mut network = net_builder();
+            network.train_epochs(&train.inputs, &train.targets, lr, epochs)?;
+            Ok(network.evaluate_batch(&holdout.inputs, &holdout.targets))
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+use crate::data_structures::Vector;
+use crate::network::NetworkError;
+use std::fs;
+
+/// Loads a comma-separated file of numeric samples, one per row, splitting
+/// the last `target_cols` columns of each row off as its target.
+///
+/// A first row that does not parse as numbers is treated as a header and
+/// skipped. Blank lines are ignored.
+pub fn load_csv(path: &str, target_cols: usize) -> Result<(Vec<Vector>, Vec<Vector>), NetworkError> {
+    parse_csv(&fs::read_to_string(path)?, target_cols)
+}
+
+/// Parses CSV contents as described for `load_csv`.
+pub fn parse_csv(contents: &str, target_cols: usize) -> Result<(Vec<Vector>, Vec<Vector>), NetworkError> {
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    let mut width = None;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let values: Result<Vec<f32>, _> = line.split(',').map(|field| field.trim().parse::<f32>()).collect();
+        let values = match values {
+            Ok(values) => values,
+            Err(_) if i == 0 => continue,
+            Err(e) => return Err(NetworkError::Parse(format!("Row {}: {}", i + 1, e))),
+        };
+
+        let expected = *width.get_or_insert(values.len());
+        if values.len() != expected {
+            return Err(NetworkError::Parse(format!(
+                "Row {} has {} columns but the first row has {}",
+                i + 1,
+                values.len(),
+                expected
+            )));
+        }
+        if values.len() <= target_cols {
+            return Err(NetworkError::Parse(format!(
+                "Row {} has {} columns, too few to split off {} target columns",
+                i + 1,
+                values.len(),
+                target_cols
+            )));
+        }
+
+        let (input, target) = values.split_at(values.len() - target_cols);
+        inputs.push(Vector::from_slice(input));
+        targets.push(Vector::from_slice(target));
+    }
+
+    Ok((inputs, targets))
+}
@@ -0,0 +1,89 @@
+use crate::data::Normalizer;
+use crate::data_structures::Vector;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// A set of samples, keeping each input paired with its target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    pub inputs: Vec<Vector>,
+    pub targets: Vec<Vector>,
+    /// The seed of the RNG used by `train_test_split`.
+    pub seed: u64,
+}
+
+impl Dataset {
+    /// Creates a dataset from parallel inputs and targets.
+    pub fn new(inputs: Vec<Vector>, targets: Vec<Vector>) -> Result<Self, String> {
+        if inputs.len() != targets.len() {
+            return Err(format!("Dataset has {} inputs but {} targets", inputs.len(), targets.len()));
+        }
+        Ok(Dataset { inputs, targets, seed: 0 })
+    }
+
+    /// Sets the seed used by `train_test_split`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Standardizes every input dimension to zero mean and unit variance in
+    /// place, returning the fitted `Normalizer` so that test data can be
+    /// transformed the same way.
+    pub fn normalize(&mut self) -> Normalizer {
+        let normalizer = Normalizer::fit(&self.inputs);
+        self.inputs = self.inputs.iter().map(|input| normalizer.apply(input)).collect();
+        normalizer
+    }
+
+    /// Shuffles the samples in place, keeping inputs and targets paired.
+    pub fn shuffle(&mut self) {
+        let order = self.permutation(&mut rand::thread_rng());
+        *self = self.select(&order);
+    }
+
+    /// Splits the shuffled samples into a training set holding `ratio` of them
+    /// and a test set holding the rest. The shuffle is seeded by `seed`, so the
+    /// same dataset always splits the same way.
+    pub fn train_test_split(&self, ratio: f32) -> (Dataset, Dataset) {
+        let order = self.permutation(&mut StdRng::seed_from_u64(self.seed));
+        let train_len = ((self.len() as f32 * ratio.clamp(0.0, 1.0)).round() as usize).min(self.len());
+        let (train, test) = order.split_at(train_len);
+        (self.select(train), self.select(test))
+    }
+
+    /// Splits the samples, in order, into `k` contiguous folds whose sizes
+    /// differ by at most one, and returns the other folds as a training set
+    /// together with fold `fold` as the holdout set.
+    pub fn fold(&self, k: usize, fold: usize) -> (Dataset, Dataset) {
+        let (base, extra) = (self.len() / k, self.len() % k);
+        let start = fold * base + fold.min(extra);
+        let end = start + base + usize::from(fold < extra);
+        let train: Vec<usize> = (0..start).chain(end..self.len()).collect();
+        let holdout: Vec<usize> = (start..end).collect();
+        (self.select(&train), self.select(&holdout))
+    }
+
+    fn permutation(&self, rng: &mut impl rand::Rng) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.shuffle(rng);
+        order
+    }
+
+    fn select(&self, indices: &[usize]) -> Dataset {
+        Dataset {
+            inputs: indices.iter().map(|&i| self.inputs[i].clone()).collect(),
+            targets: indices.iter().map(|&i| self.targets[i].clone()).collect(),
+            seed: self.seed,
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate::data_structures::Vector;
+
+/// Encodes a class label as a vector of `num_classes` zeros with a one at
+/// `label`. Panics if `label >= num_classes`.
+pub fn one_hot(label: usize, num_classes: usize) -> Vector {
+    assert!(label < num_classes, "Label {} out of range for {} classes", label, num_classes);
+    let mut encoded = Vector::zeros(num_classes);
+    encoded.elements[label] = 1.0;
+    encoded
+}
+
+/// One-hot encodes every label, for use as training targets.
+pub fn one_hot_batch(labels: &[usize], num_classes: usize) -> Vec<Vector> {
+    labels.iter().map(|&label| one_hot(label, num_classes)).collect()
+}
@@ -0,0 +1,11 @@
+pub mod cross_validation;
+pub mod csv;
+pub mod dataset;
+pub mod encoding;
+pub mod normalizer;
+
+pub use cross_validation::cross_validate;
+pub use csv::{load_csv, parse_csv};
+pub use dataset::Dataset;
+pub use encoding::{one_hot, one_hot_batch};
+pub use normalizer::Normalizer;
@@ -0,0 +1,35 @@
+use crate::data_structures::Vector;
+
+/// A per-dimension standardization `(x - mean) / std`, fitted on one set of
+/// inputs so that the same transform can be applied to any other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalizer {
+    pub means: Vector,
+    /// The population standard deviation of each dimension.
+    pub stds: Vector,
+}
+
+impl Normalizer {
+    /// Computes the mean and standard deviation of every input dimension.
+    pub fn fit(inputs: &[Vector]) -> Self {
+        let dims = inputs.first().map_or(0, Vector::len);
+        let n = inputs.len().max(1) as f32;
+        let mut means = Vector::zeros(dims);
+        for input in inputs {
+            means = means.add(input).unwrap();
+        }
+        let means = means.scalar_multiply(1.0 / n);
+        let mut variances = Vector::zeros(dims);
+        for input in inputs {
+            let centered = input.subtract(&means);
+            variances = variances.add(&centered.elementwise_multiply(&centered)).unwrap();
+        }
+        Normalizer { means, stds: variances.scalar_multiply(1.0 / n).elementwise_sqrt() }
+    }
+
+    /// Standardizes `v` with the fitted statistics. Constant dimensions are
+    /// only centered, since their standard deviation is zero.
+    pub fn apply(&self, v: &Vector) -> Vector {
+        v.subtract(&self.means).map_with_vector(&self.stds, |x, std| if std > 0.0 { x / std } else { x })
+    }
+}
@@ -0,0 +1,103 @@
+use crate::data_structures::{Float, Matrix, Vector};
+use serde::{Deserialize, Serialize};
+
+/// Batch normalization of a layer output: every feature is shifted and scaled
+/// to zero mean and unit variance, then mapped through the learnable affine
+/// transform `gamma * x̂ + beta`.
+///
+/// In training mode a batch is normalized with its own statistics, which are
+/// also folded into running estimates; single samples and evaluation mode use
+/// the running estimates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchNorm<T = f32> {
+    /// The learnable scale of each feature.
+    pub gamma: Vector<T>,
+    /// The learnable shift of each feature.
+    pub beta: Vector<T>,
+    pub running_mean: Vector<T>,
+    pub running_var: Vector<T>,
+    /// The weight of each new batch in the running estimates.
+    pub momentum: T,
+    /// Added to the variance to avoid dividing by zero.
+    pub epsilon: T,
+}
+
+impl<T: Float> BatchNorm<T> {
+    /// Creates an identity normalization over `features` features.
+    pub fn new(features: usize) -> Self {
+        BatchNorm {
+            gamma: Vector::ones(features),
+            beta: Vector::zeros(features),
+            running_mean: Vector::zeros(features),
+            running_var: Vector::ones(features),
+            momentum: T::constant(0.1),
+            epsilon: T::constant(1e-5),
+        }
+    }
+
+    /// Returns the per-feature mean and population variance of a batch with
+    /// one sample per row.
+    pub fn statistics(batch: &Matrix<T>) -> (Vector<T>, Vector<T>) {
+        let n = T::constant(batch.row_count() as f64);
+        let mut mean = Vector::zeros(batch.col_count());
+        for row in &batch.rows {
+            mean = mean.add(row).unwrap();
+        }
+        let mean = mean.scalar_multiply(T::one() / n);
+        let mut var = Vector::zeros(batch.col_count());
+        for row in &batch.rows {
+            let centered = row.subtract(&mean);
+            var = var.add(&centered.elementwise_multiply(&centered)).unwrap();
+        }
+        (mean, var.scalar_multiply(T::one() / n))
+    }
+
+    /// Normalizes `v` with the running estimates, before the affine transform.
+    pub fn normalized(&self, v: &Vector<T>) -> Vector<T> {
+        self.standardize(v, &self.running_mean, &self.running_var)
+    }
+
+    /// Normalizes a single sample with the running estimates.
+    pub fn forward(&self, v: &Vector<T>) -> Vector<T> {
+        self.affine(&self.normalized(v))
+    }
+
+    /// Normalizes a batch, with its own statistics if `use_batch_statistics`
+    /// and with the running estimates otherwise.
+    pub fn forward_batch(&self, batch: &Matrix<T>, use_batch_statistics: bool) -> Matrix<T> {
+        let (mean, var) = if use_batch_statistics {
+            BatchNorm::statistics(batch)
+        } else {
+            (self.running_mean.clone(), self.running_var.clone())
+        };
+        Matrix::new(batch.rows.iter().map(|row| self.affine(&self.standardize(row, &mean, &var))).collect())
+    }
+
+    /// Folds the statistics of `batch` into the running estimates.
+    pub fn update_running_statistics(&mut self, batch: &Matrix<T>) {
+        let (mean, var) = BatchNorm::statistics(batch);
+        let m = self.momentum;
+        self.running_mean = self.running_mean.scalar_multiply(T::one() - m).add(&mean.scalar_multiply(m)).unwrap();
+        self.running_var = self.running_var.scalar_multiply(T::one() - m).add(&var.scalar_multiply(m)).unwrap();
+    }
+
+    /// Returns the derivative of the output with respect to the input of each
+    /// feature under the running estimates, `gamma / sqrt(running_var + epsilon)`.
+    pub fn scale(&self) -> Vector<T> {
+        self.gamma.map_with_vector(&self.running_var, |g, v| g / (v + self.epsilon).sqrt())
+    }
+
+    /// Takes a gradient descent step on the scale and shift.
+    pub fn update(&mut self, gamma_grad: &Vector<T>, beta_grad: &Vector<T>, learning_rate: T) {
+        self.gamma = self.gamma.subtract(&gamma_grad.scalar_multiply(learning_rate));
+        self.beta = self.beta.subtract(&beta_grad.scalar_multiply(learning_rate));
+    }
+
+    fn standardize(&self, v: &Vector<T>, mean: &Vector<T>, var: &Vector<T>) -> Vector<T> {
+        v.subtract(mean).map_with_vector(var, |x, v| x / (v + self.epsilon).sqrt())
+    }
+
+    fn affine(&self, normalized: &Vector<T>) -> Vector<T> {
+        normalized.elementwise_multiply(&self.gamma).add(&self.beta).unwrap()
+    }
+}
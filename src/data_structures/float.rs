@@ -0,0 +1,14 @@
+use core::fmt;
+use core::iter::Sum;
+
+/// The scalar type of vectors, matrices, layers and networks: `f32`, the
+/// default everywhere, or `f64` for precision-sensitive workloads.
+pub trait Float: num_traits::Float + num_traits::NumAssign + Sum + Default + fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// Converts an `f64` constant to this type.
+    fn constant(x: f64) -> Self {
+        <Self as num_traits::NumCast>::from(x).unwrap()
+    }
+}
+
+impl Float for f32 {}
+impl Float for f64 {}
@@ -1,80 +1,551 @@
 
-use crate::data_structures::{matrix, Matrix, Vector};
+use crate::prelude::*;
+use crate::data_structures::spline::sample_points;
+use crate::data_structures::{BatchNorm, Float, Matrix, Spline, SymbolicFit, SymbolicFn, Vector};
+use crate::utils::activations::Activation;
 use serde::{Deserialize, Serialize};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use core::fmt;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The gradients of a loss with respect to the control points of every edge
+/// spline of a layer, indexed `[input][output][control point]`.
+pub type SplineGradients<T = f32> = Vec<Vec<Vec<T>>>;
 
 /// A layer in a neural network.
+///
+/// `weights` is laid out as `input_size x output_size`: row `i` holds the
+/// weights leaving input `i`, column `j` the weights feeding output `j`.
+///
+/// A layer may additionally carry a learnable spline on every edge, making it
+/// a Kolmogorov–Arnold layer: output `j` is then `Σ_i (w_ij x_i + φ_ij(x_i)) + b_j`,
+/// where the linear term acts as the residual base function of the KAN formulation.
+///
+/// The sum is batch-normalized if the layer has `batch_norm`, and the layer
+/// `activation` is then applied element-wise. A `residual` layer finally adds
+/// its input to the result.
+///
+/// The scalar type `T` defaults to `f32`. Construction, the forward pass and
+/// backpropagation work for any `Float`; initialization, evaluation and
+/// persistence are `f32` only.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Layer {
+pub struct Layer<T = f32> {
     /// The weights of the layer.
-    pub weights: Matrix,
+    pub weights: Matrix<T>,
     /// The biases of the layer.
-    pub biases: Vector,
+    pub biases: Vector<T>,
+    /// The edge splines, indexed `[input][output]` like `weights`. Empty for a
+    /// plain linear layer.
+    #[serde(default)]
+    pub splines: Vec<Vec<Spline<T>>>,
+    /// The probability of zeroing each output while the network is training.
+    #[serde(default)]
+    pub dropout: f32,
+    /// The nonlinearity applied to the layer output.
+    #[serde(default)]
+    pub activation: Activation,
+    /// The batch normalization applied before the activation, if any.
+    #[serde(default)]
+    pub batch_norm: Option<BatchNorm<T>>,
+    /// Whether the layer input is added to its output, a skip connection
+    /// that requires `input_dim == output_dim`.
+    #[serde(default)]
+    pub residual: bool,
+    /// Whether `biases` are added to the output and trained. A bias-free
+    /// layer keeps its biases at zero.
+    #[serde(default = "default_use_bias")]
+    pub use_bias: bool,
+    /// The weight gradients summed by `Network::backward_into_grads` since
+    /// the last `zero_grad`. Empty until first used.
+    #[serde(skip)]
+    pub weight_grad: Matrix<T>,
+    /// The bias gradients summed alongside `weight_grad`.
+    #[serde(skip)]
+    pub bias_grad: Vector<T>,
 }
 
-impl Layer {
+fn default_use_bias() -> bool {
+    true
+}
+
+impl<T: Float> fmt::Display for Layer<T> {
+    /// Formats a one-line shape summary, e.g. `Layer(2 -> 3)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (input_dim, output_dim) = (self.input_dim(), self.output_dim());
+        match self.splines.first().and_then(|edges| edges.first()) {
+            Some(spline) => write!(
+                f,
+                "KAN Layer({} -> {}, {} control points, degree {})",
+                input_dim,
+                output_dim,
+                spline.control_points.len(),
+                spline.degree
+            ),
+            None => write!(f, "Layer({} -> {})", input_dim, output_dim),
+        }
+    }
+}
+
+impl<T: Float> Layer<T> {
     /// Creates a new layer with the given weights and biases.
-    pub fn new(weights: Matrix, biases: Vector) -> Self {
-        Layer { weights, biases }
+    pub fn new(weights: Matrix<T>, biases: Vector<T>) -> Self {
+        Layer { weights, biases, splines: Vec::new(), dropout: 0.0, activation: Activation::Identity, batch_norm: None, residual: false, use_bias: true, weight_grad: Matrix::default(), bias_grad: Vector::default() }
     }
 
-    /// Creates a new layer with random weights and biases.
-    pub fn random(input_size: usize, output_size: usize) -> Self {
-        Layer {
-            weights: Matrix::random(input_size, output_size),
-            biases: Vector::random(output_size),
+    /// Returns the number of inputs, the row count of `weights`.
+    pub fn input_dim(&self) -> usize {
+        self.weights.row_count()
+    }
+
+    /// Returns the number of outputs, the column count of `weights`.
+    pub fn output_dim(&self) -> usize {
+        self.weights.col_count()
+    }
+
+    /// Resets the gradient buffers to zeros shaped like the weights and biases.
+    pub fn zero_grad(&mut self) {
+        self.weight_grad = Matrix::zeros(self.input_dim(), self.output_dim());
+        self.bias_grad = Vector::zeros(self.output_dim());
+    }
+
+    /// Sets the activation applied to the layer output.
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    /// Adds batch normalization of the layer output before the activation.
+    pub fn with_batch_norm(mut self) -> Self {
+        self.batch_norm = Some(BatchNorm::new(self.output_dim()));
+        self
+    }
+
+    /// Makes the layer residual, adding its input to its output. Fails unless
+    /// the layer maps `n` values to `n` values.
+    pub fn with_residual(mut self) -> Result<Self, String> {
+        if self.input_dim() != self.output_dim() {
+            return Err(format!(
+                "A residual layer needs matching input and output dimensions, got {} -> {}",
+                self.input_dim(),
+                self.output_dim()
+            ));
+        }
+        self.residual = true;
+        Ok(self)
+    }
+
+    /// Removes the biases, zeroing them and excluding them from the forward
+    /// pass and from training.
+    pub fn without_bias(mut self) -> Self {
+        self.biases = Vector::zeros(self.output_dim());
+        self.use_bias = false;
+        self
+    }
+
+    /// Adds the skip connection to `output` if the layer is residual.
+    pub fn add_residual(&self, input: &Vector<T>, output: Vector<T>) -> Vector<T> {
+        if self.residual {
+            output.add(input).unwrap()
+        } else {
+            output
+        }
+    }
+
+    /// Sets the dropout probability applied to the layer output in training mode.
+    pub fn with_dropout(mut self, dropout: f32) -> Self {
+        self.dropout = dropout;
+        self
+    }
+
+    /// Samples an inverted dropout mask for the layer output: each entry is 0
+    /// with probability `dropout` and `1 / (1 - dropout)` otherwise, so the
+    /// expected activation is unchanged.
+    #[cfg(feature = "std")]
+    pub fn dropout_mask(&self) -> Vector<T> {
+        let mut rng = rand::thread_rng();
+        let scale = T::constant(1.0 / (1.0 - self.dropout as f64));
+        Vector::new((0..self.biases.len()).map(|_| if rng.gen::<f32>() < self.dropout { T::zero() } else { scale }).collect())
+    }
+
+    /// Returns the number of trainable parameters: weights, biases unless the
+    /// layer is bias-free, the control points of any edge splines, and any
+    /// batch normalization scale and shift.
+    pub fn num_parameters(&self) -> usize {
+        let spline_parameters: usize = self.splines.iter().flatten().map(|spline| spline.control_points.len()).sum();
+        let batch_norm_parameters = self.batch_norm.as_ref().map_or(0, |bn| bn.gamma.len() + bn.beta.len());
+        let bias_parameters = if self.use_bias { self.biases.len() } else { 0 };
+        self.weights.row_count() * self.weights.col_count() + bias_parameters + spline_parameters + batch_norm_parameters
+    }
+
+    /// Returns whether the layers have the same structure and settings and
+    /// all their parameters, including edge splines and batch normalization
+    /// statistics, differ by at most `tol`. Gradient buffers are ignored.
+    pub fn equivalent(&self, other: &Layer<T>, tol: T) -> bool {
+        let close = |a: &[T], b: &[T]| a.len() == b.len() && a.iter().zip(b).all(|(&x, &y)| (x - y).abs() <= tol);
+        let splines_close = self.splines.len() == other.splines.len()
+            && self.splines.iter().zip(&other.splines).all(|(a, b)| {
+                a.len() == b.len()
+                    && a.iter().zip(b).all(|(a, b)| a.degree == b.degree && close(&a.knots, &b.knots) && close(&a.control_points, &b.control_points))
+            });
+        let batch_norm_close = match (&self.batch_norm, &other.batch_norm) {
+            (None, None) => true,
+            (Some(a), Some(b)) => {
+                a.momentum == b.momentum
+                    && a.epsilon == b.epsilon
+                    && [(&a.gamma, &b.gamma), (&a.beta, &b.beta), (&a.running_mean, &b.running_mean), (&a.running_var, &b.running_var)]
+                        .iter()
+                        .all(|(a, b)| a.approx_eq(b, tol))
+            }
+            _ => false,
+        };
+        self.activation == other.activation
+            && self.residual == other.residual
+            && self.use_bias == other.use_bias
+            && self.dropout == other.dropout
+            && self.weights.approx_eq(&other.weights, tol)
+            && self.biases.approx_eq(&other.biases, tol)
+            && splines_close
+            && batch_norm_close
+    }
+
+    /// Sums the edge spline outputs `Σ_i φ_ij(x_i)` for every output `j`.
+    pub fn spline_forward(&self, input: &Vector<T>) -> Vector<T> {
+        let mut output = Vector::zeros(self.biases.len());
+        for (x, splines) in input.elements.iter().zip(&self.splines) {
+            for (o, spline) in output.elements.iter_mut().zip(splines) {
+                *o += spline.evaluate(*x);
+            }
+        }
+        output
+    }
+
+    /// Computes the layer output before the activation is applied.
+    pub fn pre_activation(&self, input: &Vector<T>) -> Vector<T> {
+        let mut output = self.weights.transpose().multiply_with_vector(input).unwrap();
+        if self.use_bias {
+            output = output.add(&self.biases).unwrap();
+        }
+        if !self.splines.is_empty() {
+            output = output.add(&self.spline_forward(input)).unwrap();
+        }
+        output
+    }
+
+    /// Applies the layer's batch normalization, using its running estimates, or
+    /// returns `z` unchanged if it has none.
+    pub fn normalize(&self, z: &Vector<T>) -> Vector<T> {
+        match &self.batch_norm {
+            Some(batch_norm) => batch_norm.forward(z),
+            None => z.clone(),
         }
     }
 
     /// Performs the forward propagation of the layer.
-    pub fn forward(&self, input: &Vector) -> Vector {
-        // Compute the dot product of weights and input, then add biases
-        println!("Forward");
-        println!("input: {:?}", input);
-        // println!("weights: {:?}", self.weights);
-        // println!("biases: {:?}", self.biases);
-        let output = self.weights.multiply_with_vector(input).unwrap().add(&self.biases).unwrap();
-
-
-        println!("output: {:?}", output);
-        
-        output.unwrap()
-    }
-
-    /// Performs the backward propagation of the layer.
-    pub fn backward(&self, input: &Vector, output: &Vector, target: &Vector) -> (Matrix, Vector) {
-        println!("Backward");
-        // println!("input: {:?}", input);
-        // println!("target: {:?}", target);
-        // Compute the error and gradient
-        println!("output: {:?}", output);
-        let error = output.subtract(target);
-        println!("error: {:?}", error);
-        let gradient = output.elementwise_multiply(&output.subtract(&Vector::ones(output.len())));
-        // println!("gradient: {:?}", gradient);
-        let delta = self.delta(&error, &gradient);
-        // println!("delta: {:?}", delta);
-        let weight_gradients = self.weight_gradients(input, output, &delta);
-        // println!("weight_gradients: {:?}", weight_gradients);
-        (weight_gradients, delta)
+    pub fn forward(&self, input: &Vector<T>) -> Vector<T> {
+        // Compute the dot product of weights and input, add biases, then activate
+        self.add_residual(input, self.activation.apply(&self.normalize(&self.pre_activation(input))))
+    }
+
+    /// Computes the layer output for a batch of inputs, one sample per row,
+    /// before normalization and activation.
+    pub fn pre_activation_batch(&self, inputs: &Matrix<T>) -> Matrix<T> {
+        let outputs = inputs.multiply(&self.weights).unwrap();
+        let rows = outputs
+            .rows
+            .iter()
+            .zip(&inputs.rows)
+            .map(|(output, input)| {
+                let output = if self.use_bias { output.add(&self.biases).unwrap() } else { output.clone() };
+                if self.splines.is_empty() {
+                    output
+                } else {
+                    output.add(&self.spline_forward(input)).unwrap()
+                }
+            })
+            .collect();
+        Matrix::new(rows)
+    }
+
+    /// Performs the forward propagation of the layer for a batch of inputs, one
+    /// sample per row. Batch normalization uses its running estimates.
+    pub fn forward_batch(&self, inputs: &Matrix<T>) -> Matrix<T> {
+        self.finish_batch(inputs, self.pre_activation_batch(inputs), false)
+    }
+
+    /// Like `forward_batch`, but batch normalization uses the statistics of
+    /// the batch itself, as during training.
+    pub fn forward_batch_training(&self, inputs: &Matrix<T>) -> Matrix<T> {
+        self.finish_batch(inputs, self.pre_activation_batch(inputs), true)
+    }
+
+    fn finish_batch(&self, inputs: &Matrix<T>, pre_activations: Matrix<T>, use_batch_statistics: bool) -> Matrix<T> {
+        let pre_activations = match &self.batch_norm {
+            Some(batch_norm) => batch_norm.forward_batch(&pre_activations, use_batch_statistics),
+            None => pre_activations,
+        };
+        Matrix::new(pre_activations.rows.iter().zip(&inputs.rows).map(|(row, input)| self.add_residual(input, self.activation.apply(row))).collect())
+    }
+
+    /// Moves every weight and spline control point towards zero by `amount`
+    /// times its sign: one subgradient step on an L1 penalty. Zero parameters
+    /// stay at zero.
+    pub fn l1_step(&mut self, amount: T) {
+        let sign = |x: T| if x == T::zero() { T::zero() } else { x.signum() };
+        self.weights = self.weights.map(|w| w - amount * sign(w));
+        for spline in self.splines.iter_mut().flatten() {
+            spline.control_points.iter_mut().for_each(|c| *c -= amount * sign(*c));
+        }
     }
 
+    /// Returns the L1 norm of every edge, `|w_ij| + Σ |c_ij|` over its weight
+    /// and spline control points, indexed `[input][output]`.
+    pub fn edge_norms(&self) -> Vec<Vec<T>> {
+        self.weights
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.elements
+                    .iter()
+                    .enumerate()
+                    .map(|(j, w)| {
+                        let spline_norm: T = self.splines.get(i).and_then(|edges| edges.get(j)).map_or(T::zero(), |spline| spline.control_points.iter().map(|c| c.abs()).sum());
+                        w.abs() + spline_norm
+                    })
+                    .collect()
+            })
+            .collect()
+    }
 
-    /// Updates the weights and biases of the layer based on the gradients and learning rate.
-    pub fn update(&mut self, weight_gradients: &Matrix, delta: &Vector, learning_rate: f32) {
-        let weight_gradients = weight_gradients.scalar_multiply(learning_rate);
-        self.weights = self.weights.subtract(&weight_gradients).unwrap();
-        self.biases = self.biases.subtract(&delta).unwrap().unwrap()
+    /// Returns the entropy `-Σ p log p` of the edge norms normalized to sum to
+    /// one. It is low when a few edges carry most of the layer.
+    pub fn edge_entropy(&self) -> T {
+        let norms: Vec<T> = self.edge_norms().into_iter().flatten().collect();
+        let total: T = norms.iter().copied().sum();
+        if total == T::zero() {
+            return T::zero();
+        }
+        norms.iter().filter(|&&n| n > T::zero()).map(|&n| -(n / total) * (n / total).ln()).sum()
+    }
+
+    /// Takes one gradient descent step of size `amount` on `edge_entropy`.
+    /// The entropy gradient with respect to an edge norm `n` is
+    /// `-(log p + H) / Σ n`, and the norm moves every parameter of the edge
+    /// along its sign. Zero parameters stay at zero.
+    pub fn entropy_step(&mut self, amount: T) {
+        let norms = self.edge_norms();
+        let total: T = norms.iter().flatten().copied().sum();
+        if total == T::zero() {
+            return;
+        }
+        let entropy = self.edge_entropy();
+        let sign = |x: T| if x == T::zero() { T::zero() } else { x.signum() };
+        for (i, edge_norms) in norms.iter().enumerate() {
+            for (j, &norm) in edge_norms.iter().enumerate() {
+                if norm == T::zero() {
+                    continue;
+                }
+                let gradient = -((norm / total).ln() + entropy) / total;
+                let w = &mut self.weights.rows[i].elements[j];
+                *w -= amount * gradient * sign(*w);
+                if let Some(spline) = self.splines.get_mut(i).and_then(|edges| edges.get_mut(j)) {
+                    spline.control_points.iter_mut().for_each(|c| *c -= amount * gradient * sign(*c));
+                }
+            }
+        }
+    }
+
+    /// Computes the gradient of each edge spline's control points for the given
+    /// layer input and output delta: `δ_j B_k(x_i)` for control point `k` of
+    /// the edge from input `i` to output `j`. Empty for a plain linear layer.
+    pub fn spline_gradients(&self, input: &Vector<T>, delta: &Vector<T>) -> SplineGradients<T> {
+        self.splines
+            .iter()
+            .zip(&input.elements)
+            .map(|(splines, &x)| {
+                splines
+                    .iter()
+                    .zip(&delta.elements)
+                    .map(|(spline, &d)| spline.basis_values(x).iter().map(|&b| d * b).collect())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Takes a gradient descent step on the spline control points.
+    pub fn update_splines(&mut self, gradients: &SplineGradients<T>, learning_rate: T) {
+        for (spline, gradient) in self.splines.iter_mut().flatten().zip(gradients.iter().flatten()) {
+            for (c, g) in spline.control_points.iter_mut().zip(gradient) {
+                *c -= learning_rate * *g;
+            }
+        }
+    }
+
+    /// Propagates a delta on the layer output back to its input, following both
+    /// the linear weights and the edge splines.
+    pub fn input_gradient(&self, input: &Vector<T>, delta: &Vector<T>) -> Vector<T> {
+        // The forward pass applies `weights^T`, so the delta flows back
+        // through its transpose, which is `weights` itself.
+        let mut gradient = self.weights.multiply_with_vector(delta).unwrap();
+        for ((g, x), splines) in gradient.elements.iter_mut().zip(&input.elements).zip(&self.splines) {
+            *g += splines.iter().zip(&delta.elements).map(|(spline, &d)| spline.derivative(*x) * d).sum::<T>();
+        }
+        gradient
+    }
+
+    /// Backpropagates `upstream_delta`, the loss gradient with respect to the
+    /// layer output, through one layer for the given input. Returns the
+    /// gradient with respect to the input, for the previous layer, and the
+    /// weight and bias gradients. Batch normalization uses its running
+    /// estimates, as in `forward`.
+    pub fn backward(&self, input: &Vector<T>, upstream_delta: &Vector<T>) -> (Vector<T>, Matrix<T>, Vector<T>) {
+        let z = self.normalize(&self.pre_activation(input));
+        let (input_delta, delta, _) = self.backward_from(input, &z, upstream_delta);
+        (input_delta, self.weight_gradients(input, &delta), delta)
+    }
+
+    /// The core of `backward`, given the input `z` of the activation recorded
+    /// by the forward pass. Returns the input gradient, the delta of the
+    /// pre-activation, which is the bias gradient, and the delta of `z`,
+    /// which the batch normalization gradients need.
+    pub(crate) fn backward_from(&self, input: &Vector<T>, z: &Vector<T>, upstream_delta: &Vector<T>) -> (Vector<T>, Vector<T>, Vector<T>) {
+        let activation_delta = upstream_delta.elementwise_multiply(&self.activation.derivative(z));
+        let delta = match &self.batch_norm {
+            Some(batch_norm) => activation_delta.elementwise_multiply(&batch_norm.scale()),
+            None => activation_delta.clone(),
+        };
+        // The skip connection of a residual layer passes the output delta
+        // straight back to its input.
+        let input_delta = self.add_residual(upstream_delta, self.input_gradient(input, &delta));
+        (input_delta, delta, activation_delta)
+    }
+
+    /// Updates the weights and biases of the layer based on the gradients and
+    /// learning rate. The biases of a bias-free layer are left unchanged.
+    pub fn update(&mut self, weight_gradients: &Matrix<T>, delta: &Vector<T>, learning_rate: T) {
+        self.weights.sub_mut(&weight_gradients.scalar_multiply(learning_rate)).unwrap();
+        if self.use_bias {
+            self.biases = self.biases.subtract(&delta.scalar_multiply(learning_rate));
+        }
     }
 
     /// Trains the layer on a single input and target.
-    pub fn train(&mut self, input: &Vector, target: &Vector, learning_rate: f32) {
+    pub fn train(&mut self, input: &Vector<T>, target: &Vector<T>, learning_rate: T) {
         let output = self.forward(input);
-        let (weight_gradients, delta) = self.backward(input, &output, target);
+        let (_, weight_gradients, delta) = self.backward(input, &output.subtract(target));
         self.update(&weight_gradients, &delta, learning_rate);
     }
 
+    pub fn delta(&self, error: &Vector<T>, gradient: &Vector<T>) -> Vector<T> {
+        error.elementwise_multiply(gradient)
+    }
+
+    /// Computes the weight gradients for the given layer input and delta,
+    /// laid out like `weights` (`input_size x output_size`).
+    pub fn weight_gradients(&self, input: &Vector<T>, gradient: &Vector<T>) -> Matrix<T> {
+        input.outer_product(gradient)
+    }
+}
+
+impl Layer {
+    /// Creates a new layer with random weights and biases.
+    #[cfg(feature = "std")]
+    pub fn random(input_size: usize, output_size: usize) -> Self {
+        Layer::random_with_rng(input_size, output_size, &mut rand::thread_rng())
+    }
+
+    /// Creates a new layer with random weights and biases drawn from `rng`.
+    pub fn random_with_rng(input_size: usize, output_size: usize, rng: &mut impl Rng) -> Self {
+        Layer {
+            weights: Matrix::random_with_rng(input_size, output_size, rng),
+            biases: Vector::random_with_rng(output_size, rng),
+            splines: Vec::new(),
+            dropout: 0.0,
+            activation: Activation::Identity,
+            batch_norm: None,
+            residual: false,
+            use_bias: true,
+            weight_grad: Matrix::default(),
+            bias_grad: Vector::default(),
+        }
+    }
+
+    /// Creates a new layer with random weights and biases that are fully
+    /// determined by `seed`.
+    pub fn new_seeded(input_dim: usize, output_dim: usize, seed: u64) -> Self {
+        Layer::random_with_rng(input_dim, output_dim, &mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Creates a new layer with Xavier (Glorot) initialized weights, drawn from
+    /// `N(0, 2 / (input_dim + output_dim))`, and zero biases.
+    #[cfg(feature = "std")]
+    pub fn new_xavier(input_dim: usize, output_dim: usize) -> Self {
+        let std_dev = (2.0 / (input_dim + output_dim) as f32).sqrt();
+        Layer::new(Matrix::random_normal(input_dim, output_dim, std_dev), Vector::zeros(output_dim))
+    }
+
+    /// Creates a new layer with He initialized weights, drawn from
+    /// `N(0, 2 / input_dim)`, and zero biases. Suited to ReLU-like activations.
+    #[cfg(feature = "std")]
+    pub fn new_he(input_dim: usize, output_dim: usize) -> Self {
+        let std_dev = (2.0 / input_dim as f32).sqrt();
+        Layer::new(Matrix::random_normal(input_dim, output_dim, std_dev), Vector::zeros(output_dim))
+    }
+
+    /// Creates a new Kolmogorov–Arnold layer with random weights and biases and
+    /// a random spline of `grid_size` intervals over `[-1, 1]` on every edge.
+    #[cfg(feature = "std")]
+    pub fn kan(input_size: usize, output_size: usize, grid_size: usize, degree: usize) -> Self {
+        let splines = (0..input_size)
+            .map(|_| (0..output_size).map(|_| Spline::random(grid_size, degree, -1.0, 1.0)).collect())
+            .collect();
+        Layer {
+            splines,
+            ..Layer::random(input_size, output_size)
+        }
+    }
+
+    /// Zeroes every weight whose magnitude is below `threshold`, and the
+    /// control points of every edge spline whose largest one is.
+    pub fn prune(&mut self, threshold: f32) {
+        self.weights = self.weights.map(|w| if w.abs() < threshold { 0.0 } else { w });
+        for spline in self.splines.iter_mut().flatten() {
+            if spline.control_points.iter().all(|c| c.abs() < threshold) {
+                spline.control_points.iter_mut().for_each(|c| *c = 0.0);
+            }
+        }
+    }
+
+    /// Refines the grid of every edge spline to `new_grid_size` intervals.
+    pub fn refine_grid(&mut self, new_grid_size: usize) {
+        self.splines.iter_mut().flatten().for_each(|spline| spline.refine_grid(new_grid_size));
+    }
+
+    /// Fits the best of `candidates` to the whole edge from input `i` to output
+    /// `j`, `w_ij x + φ_ij(x)`, over the spline domain (`[-1, 1]` for a plain
+    /// linear layer).
+    pub fn fit_symbolic_edge(&self, i: usize, j: usize, candidates: &[SymbolicFn]) -> Option<SymbolicFit> {
+        let weight = self.weights.rows[i].elements[j];
+        let spline = self.splines.get(i).and_then(|edges| edges.get(j));
+        let (min, max) = spline.map_or((-1.0, 1.0), Spline::domain);
+        let xs = sample_points(min, max);
+        let ys: Vec<f32> = xs.iter().map(|&x| weight * x + spline.map_or(0.0, |s| s.evaluate(x))).collect();
+        crate::data_structures::symbolic::fit_best(&xs, &ys, candidates)
+    }
+
+    /// Samples the spline `φ_ij` on the edge from input `in_idx` to output
+    /// `out_idx` at `samples` evenly spaced points spanning its domain,
+    /// returning `(x, φ(x))` pairs for plotting. Panics if the edge has no
+    /// spline.
+    pub fn spline_curve(&self, in_idx: usize, out_idx: usize, samples: usize) -> Vec<(f32, f32)> {
+        let spline = self.splines.get(in_idx).and_then(|edges| edges.get(out_idx)).unwrap_or_else(|| {
+            panic!("No edge spline from input {} to output {} in a layer with {} spline rows", in_idx, out_idx, self.splines.len())
+        });
+        let (min, max) = spline.domain();
+        let last = samples.saturating_sub(1).max(1) as f32;
+        (0..samples).map(|s| min + (max - min) * s as f32 / last).map(|x| (x, spline.evaluate(x))).collect()
+    }
+
     /// Predicts the output of the layer for a given input.
     pub fn predict(&self, input: &Vector) -> Vector {
         self.forward(input)
@@ -176,14 +647,25 @@ impl Layer {
         (self.loss_batch(inputs, targets), self.accuracy_batch(inputs, targets))
     }
 
+    #[cfg(feature = "std")]
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
+        Layer::try_from_str(s).unwrap()
+    }
+
+    /// Parses a layer from the `to_str` format, returning an error message
+    /// for missing or malformed lines.
+    #[cfg(feature = "std")]
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
         let mut lines = s.lines();
-        let weights = Matrix::from_string(lines.next().unwrap()).unwrap();  
-        let biases = Vector::from_string(lines.next().unwrap())
-            .unwrap();  
-        Layer { weights, biases }
+        let weights = lines.next().ok_or("Missing weights line")?;
+        let weights = Matrix::from_string(weights).map_err(|e| format!("Invalid weights: {}", e))?;
+        let biases = lines.next().ok_or("Missing biases line")?;
+        let biases = Vector::from_string(biases).map_err(|e| format!("Invalid biases: {}", e))?;
+        Ok(Layer::new(weights, biases))
     }
 
+    #[cfg(feature = "std")]
     pub fn to_str(&self) -> String {
         let weights = self.weights.to_string();
         let biases = self.biases.to_string();
@@ -195,22 +677,9 @@ impl Layer {
     }
 
     pub fn update_biases(&mut self, learning_rate: f32) {
-        self.biases = self.biases.scalar_multiply(learning_rate);
-    }
-
-    pub fn delta(&self, error: &Vector, gradient: &Vector) -> Vector {
-        error.elementwise_multiply(gradient)
-    }
-
-    pub fn weight_gradients(&self, input: &Vector, output: &Vector, gradient: &Vector) -> Matrix {
-        let cols = self.weights.col_count();
-        let rows = self.weights.row_count();
-        let mut weight_gradients = Matrix::zeros(cols, rows);
-        for i in 0..cols {
-            for j in 0..rows {
-                weight_gradients.set_element(i, j, input.get_element(i) * gradient.get_element(j)).unwrap();
-            }
+        if !self.use_bias {
+            return;
         }
-        weight_gradients
+        self.biases = self.biases.scalar_multiply(learning_rate);
     }
 }
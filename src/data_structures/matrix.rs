@@ -1,19 +1,83 @@
-use crate::data_structures::Vector;
+use crate::prelude::*;
+use crate::data_structures::{Float, Vector};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use rand_distr::{Distribution, Normal};
+use core::cmp::Ordering;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::fs;
-use std::process::exit;
-use std::slice::{Iter, IterMut};
+use core::ops::Sub;
+use core::slice::{Iter, IterMut};
+
+/// A dense matrix of `T` elements stored as row vectors, `f32` unless stated
+/// otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Matrix<T = f32> {
+    pub rows: Vec<Vector<T>>,
+}
+
+impl<T: Float> fmt::Display for Matrix<T> {
+    /// Formats one bracketed row per line with the columns right-aligned,
+    /// honouring any requested precision.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.elements
+                    .iter()
+                    .map(|x| match f.precision() {
+                        Some(precision) => format!("{:.*}", precision, x),
+                        None => format!("{}", x),
+                    })
+                    .collect()
+            })
+            .collect();
+        let width = cells.iter().flatten().map(String::len).max().unwrap_or(0);
+
+        for (i, row) in cells.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let row: Vec<String> = row.iter().map(|cell| format!("{:>width$}", cell, width = width)).collect();
+            write!(f, "[{}]", row.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Float> Sub for Matrix<T> {
+    type Output = Matrix<T>;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Matrix {
-    pub rows: Vec<Vector>,
+    /// Subtracts element-wise. Panics if the shapes differ.
+    fn sub(mut self, other: Matrix<T>) -> Matrix<T> {
+        self.sub_mut(&other).unwrap();
+        self
+    }
 }
 
-impl Matrix {
-    pub fn new(rows: Vec<Vector>) -> Self {
+impl<T: Float> Matrix<T> {
+    pub fn new(rows: Vec<Vector<T>>) -> Self {
         Matrix { rows }
     }
 
+    /// Creates a matrix from row vectors, returning an error if they do not all
+    /// have the same length.
+    pub fn from_rows(rows: Vec<Vector<T>>) -> Result<Self, String> {
+        if let Some(first) = rows.first() {
+            if let Some((i, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != first.len()) {
+                return Err(format!(
+                    "Row {} has length {} but the first row has length {}",
+                    i,
+                    row.len(),
+                    first.len()
+                ));
+            }
+        }
+        Ok(Matrix::new(rows))
+    }
+
     pub fn row_count(&self) -> usize {
         self.rows.len()
     }
@@ -22,152 +86,179 @@ impl Matrix {
         self.rows.first().map_or(0, |row| row.len())
     }
 
-    pub fn multiply(&self, other: &Matrix) -> Result<Matrix, &'static str> {
+    /// Computes the matrix product `self · other` of an `m x k` and a `k x n`
+    /// matrix, returning an error if the inner dimensions differ.
+    pub fn multiply(&self, other: &Matrix<T>) -> Result<Matrix<T>, String> {
         if self.col_count() != other.row_count() {
-            print!(
+            return Err(format!(
                 "Matrix dimensions do not match for multiplication: {}x{} and {}x{}",
                 self.row_count(),
                 self.col_count(),
                 other.row_count(),
                 other.col_count()
-            );
-            return Err("Matrix dimensions do not match for multiplication");
+            ));
         }
-        
-        let col_count = other.col_count();
-        let mut result = Vec::with_capacity(self.row_count());
-        
-        for row in &self.rows {
-            let mut new_row = Vec::with_capacity(col_count);
-            for col in 0..col_count {
-                let col_vec: Vec<f32> = other.rows.iter().map(|r| r.elements[col]).collect();
-                let dot_product = row.dot(&Vector::new(col_vec));
-                new_row.push(dot_product);
-            }
-            result.push(Vector::new(new_row));
-        }
-        
+
+        let cols = other.cols();
+        let result = self
+            .rows
+            .iter()
+            .map(|row| Vector::new(cols.iter().map(|col| row.dot(col).unwrap()).collect()))
+            .collect();
+
         Ok(Matrix::new(result))
     }
 
-    pub fn multiply_with_vector(&self, other: &Vector) -> Result<Vector, &'static str> {
+    pub fn multiply_with_vector(&self, other: &Vector<T>) -> Result<Vector<T>, &'static str> {
         if self.col_count() != other.len() {
-            print!(
-                "Matrix and vector dimensions do not match for multiplication: {}x{} and {}",
-                self.row_count(),
-                self.col_count(),
-                other.len()
-                );
             return Err("Matrix and vector dimensions do not match for multiplication");
         }
 
         let mut result = Vec::with_capacity(self.row_count());
         for row in &self.rows {
-            let dot_product = row.dot(other);
+            let dot_product = row.dot(other).unwrap();
             result.push(dot_product);
         }
         Ok(Vector::new(result)) 
     }
 
-    pub fn add(&self, other: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn add(&self, other: &Matrix<T>) -> Result<Matrix<T>, &'static str> {
         if self.row_count() != other.row_count() || self.col_count() != other.col_count() {
-            print!(
-                "Matrix dimensions do not match for addition: {}x{} and {}x{}",
-                self.row_count(),
-                self.col_count(),
-                other.row_count(),
-                other.col_count()
-            );
             return Err("Matrix dimensions do not match for addition");
         }
 
-        let rows: Vec<Vector> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.add(b)).collect();
-        Ok(Matrix::new(rows))
+        let rows: Result<Vec<Vector<T>>, String> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.add(b)).collect();
+        rows.map(Matrix::new).map_err(|_| "Matrix dimensions do not match for addition")
     }
 
-    pub fn add_scalar(&self, scalar: f32) -> Matrix {
-        let rows: Vec<Vector> = self.rows.iter().map(|r| r.add_scalar(scalar)).collect();
+    pub fn add_scalar(&self, scalar: T) -> Matrix<T> {
+        let rows: Vec<Vector<T>> = self.rows.iter().map(|r| r.add_scalar(scalar)).collect();
         Matrix::new(rows)
     }
 
-    pub fn subtract(&self, other: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn subtract(&self, other: &Matrix<T>) -> Result<Matrix<T>, &'static str> {
         if self.row_count() != other.row_count() || self.col_count() != other.col_count() {
             return Err("Matrix dimensions do not match for subtraction");
         }
 
-        let rows: Vec<Vector> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.subtract(b)).collect();
+        let rows: Vec<Vector<T>> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.subtract(b)).collect();
         Ok(Matrix::new(rows))
     }
 
-    pub fn scalar_multiply(&self, scalar: f32) -> Matrix {
-        let rows: Vec<Vector> = self.rows.iter().map(|r| r.scalar_multiply(scalar)).collect();
+    pub fn scalar_multiply(&self, scalar: T) -> Matrix<T> {
+        let rows: Vec<Vector<T>> = self.rows.iter().map(|r| r.scalar_multiply(scalar)).collect();
         Matrix::new(rows)
     }
 
-    pub fn elementwise_multiply(&self, other: &Matrix) -> Result<Matrix, &'static str> {
+    /// Multiplies every element by `scalar` in place.
+    pub fn scalar_multiply_mut(&mut self, scalar: T) {
+        self.rows.iter_mut().for_each(|row| *row *= scalar);
+    }
+
+    /// Adds `other` element-wise in place.
+    pub fn add_mut(&mut self, other: &Matrix<T>) -> Result<(), String> {
+        self.zip_mut(other, "addition", |a, b| *a += b)
+    }
+
+    /// Subtracts `other` element-wise in place.
+    pub fn sub_mut(&mut self, other: &Matrix<T>) -> Result<(), String> {
+        self.zip_mut(other, "subtraction", |a, b| *a -= b)
+    }
+
+    fn zip_mut(&mut self, other: &Matrix<T>, operation: &str, f: impl Fn(&mut T, T)) -> Result<(), String> {
+        if self.shape() != other.shape() {
+            return Err(format!(
+                "Matrix dimensions do not match for {}: {}x{} and {}x{}",
+                operation,
+                self.row_count(),
+                self.col_count(),
+                other.row_count(),
+                other.col_count()
+            ));
+        }
+
+        for (row, other_row) in self.rows.iter_mut().zip(&other.rows) {
+            row.elements.iter_mut().zip(&other_row.elements).for_each(|(a, &b)| f(a, b));
+        }
+        Ok(())
+    }
+
+    pub fn elementwise_multiply(&self, other: &Matrix<T>) -> Result<Matrix<T>, &'static str> {
         if self.row_count() != other.row_count() || self.col_count() != other.col_count() {
             return Err("Matrix dimensions do not match for element-wise multiplication");
         }
 
-        let rows: Vec<Vector> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.elementwise_multiply(b)).collect();
+        let rows: Vec<Vector<T>> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.elementwise_multiply(b)).collect();
         Ok(Matrix::new(rows))
     }
 
-    pub fn elementwise_divide(&self, other: &Matrix) -> Result<Matrix, &'static str> {
+    /// Returns the element-wise (Hadamard) product, failing if the shapes differ.
+    pub fn hadamard(&self, other: &Matrix<T>) -> Result<Matrix<T>, String> {
+        let mut product = self.clone();
+        product.zip_mut(other, "Hadamard product", |a, b| *a *= b)?;
+        Ok(product)
+    }
+
+    pub fn elementwise_divide(&self, other: &Matrix<T>) -> Result<Matrix<T>, &'static str> {
         if self.row_count() != other.row_count() || self.col_count() != other.col_count() {
             return Err("Matrix dimensions do not match for element-wise division");
         }
 
-        let rows: Vec<Vector> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.elementwise_divide(b)).collect();
+        let rows: Vec<Vector<T>> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.elementwise_divide(b)).collect();
         Ok(Matrix::new(rows))
     }
 
-    pub fn elementwise_sqrt(&self) -> Matrix {
-        let rows: Vec<Vector> = self.rows.iter().map(Vector::elementwise_sqrt).collect();
+    pub fn elementwise_sqrt(&self) -> Matrix<T> {
+        let rows: Vec<Vector<T>> = self.rows.iter().map(Vector::elementwise_sqrt).collect();
         Matrix::new(rows)
     }
 
-    pub fn transpose(&self) -> Matrix {
-        let row_count = self.row_count();
-        let col_count = self.col_count();
-        let mut transposed = vec![vec![0.0; row_count]; col_count];
-        
-        for i in 0..row_count {
-            for j in 0..col_count {
-                transposed[j][i] = self.rows[i].elements[j];
+    /// Returns the transpose of the matrix, turning an `m x n` matrix into an
+    /// `n x m` one.
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut transposed = Matrix::zeros(self.col_count(), self.row_count());
+
+        for i in 0..self.row_count() {
+            for j in 0..self.col_count() {
+                transposed.set_element(j, i, self.get_element(i, j).unwrap()).unwrap();
             }
         }
-        
-        Matrix::new(transposed.into_iter().map(Vector::new).collect())
-    }
 
-    pub fn random(row_count: usize, col_count: usize) -> Matrix {
-        let rows: Vec<Vector> = (0..row_count).map(|_| Vector::random(col_count)).collect();
-        Matrix::new(rows)
+        transposed
     }
 
-    pub fn to_vec(&self) -> Vec<Vec<f32>> {
+    pub fn to_vec(&self) -> Vec<Vec<T>> {
         self.rows.iter().map(Vector::to_vec).collect()
     }
 
-    pub fn from_vec(vec: Vec<Vec<f32>>) -> Matrix {
+    pub fn from_vec(vec: Vec<Vec<T>>) -> Matrix<T> {
         Matrix::new(vec.into_iter().map(Vector::new).collect())
     }
 
-    pub fn get_row(&self, index: usize) -> Option<Vector> {
+    /// Returns a copy of row `i`. Panics if `i` is out of bounds.
+    pub fn row(&self, i: usize) -> Vector<T> {
+        self.rows[i].clone()
+    }
+
+    /// Returns a copy of column `j`. Panics if `j` is out of bounds.
+    pub fn col(&self, j: usize) -> Vector<T> {
+        self.get_col(j).unwrap()
+    }
+
+    pub fn get_row(&self, index: usize) -> Option<Vector<T>> {
         self.rows.get(index).cloned()
     }
 
-    pub fn get_col(&self, index: usize) -> Result<Vector, &'static str> {
+    pub fn get_col(&self, index: usize) -> Result<Vector<T>, &'static str> {
         if index >= self.col_count() {
             return Err("Column index out of bounds");
         }
 
-        let col: Vec<f32> = self.rows.iter().map(|r| r.elements[index]).collect();
+        let col: Vec<T> = self.rows.iter().map(|r| r.elements[index]).collect();
         Ok(Vector::new(col))
     }
 
-    pub fn set_row(&mut self, index: usize, row: Vector) -> Result<(), &'static str> {
+    pub fn set_row(&mut self, index: usize, row: Vector<T>) -> Result<(), &'static str> {
         if index >= self.row_count() {
             return Err("Row index out of bounds");
         }
@@ -180,7 +271,7 @@ impl Matrix {
         Ok(())
     }
 
-    pub fn set_col(&mut self, index: usize, col: Vector) -> Result<(), &'static str> {
+    pub fn set_col(&mut self, index: usize, col: Vector<T>) -> Result<(), &'static str> {
         if index >= self.col_count() {
             return Err("Column index out of bounds");
         }
@@ -196,7 +287,19 @@ impl Matrix {
         Ok(())
     }
 
-    pub fn set_element(&mut self, row: usize, col: usize, value: f32) -> Result<(), &'static str> {
+    pub fn get_element(&self, row: usize, col: usize) -> Result<T, &'static str> {
+        if row >= self.row_count() {
+            return Err("Row index out of bounds");
+        }
+
+        if col >= self.col_count() {
+            return Err("Column index out of bounds");
+        }
+
+        Ok(self.rows[row].elements[col])
+    }
+
+    pub fn set_element(&mut self, row: usize, col: usize, value: T) -> Result<(), &'static str> {
         if row >= self.row_count() {
             return Err("Row index out of bounds");
         }
@@ -209,32 +312,42 @@ impl Matrix {
         Ok(())
     }
 
-    pub fn map(&self, f: impl Fn(f32) -> f32) -> Matrix {
-        let rows: Vec<Vector> = self.rows.iter().map(|r| r.map(&f)).collect();
+    pub fn map(&self, f: impl Fn(T) -> T) -> Matrix<T> {
+        let rows: Vec<Vector<T>> = self.rows.iter().map(|r| r.map(&f)).collect();
         Matrix::new(rows)
     }
 
-    pub fn map_with_index(&self, f: impl Fn(f32, usize) -> f32) -> Matrix {
-        let rows: Vec<Vector> = self.rows.iter().enumerate().map(|(i, r)| r.map_with_index(|e, j| f(e, j))).collect();
+    /// Applies `f` to every element, like `map`.
+    pub fn apply<F: Fn(T) -> T>(&self, f: F) -> Matrix<T> {
+        self.map(f)
+    }
+
+    /// Clamps every element into `[min, max]`. Panics if `min > max`.
+    pub fn clamp(&self, min: T, max: T) -> Matrix<T> {
+        Matrix::new(self.rows.iter().map(|row| row.clamp(min, max)).collect())
+    }
+
+    pub fn map_with_index(&self, f: impl Fn(T, usize) -> T) -> Matrix<T> {
+        let rows: Vec<Vector<T>> = self.rows.iter().map(|r| r.map_with_index(&f)).collect();
         Matrix::new(rows)
     }
 
-    pub fn map_with_matrix(&self, other: &Matrix, f: impl Fn(f32, f32) -> f32) -> Result<Matrix, &'static str> {
+    pub fn map_with_matrix(&self, other: &Matrix<T>, f: impl Fn(T, T) -> T) -> Result<Matrix<T>, &'static str> {
         if self.row_count() != other.row_count() || self.col_count() != other.col_count() {
             return Err("Matrix dimensions do not match for element-wise mapping");
         }
 
-        let rows: Vec<Vector> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.map_with_vector(b, &f)).collect();
+        let rows: Vec<Vector<T>> = self.rows.iter().zip(&other.rows).map(|(a, b)| a.map_with_vector(b, &f)).collect();
         Ok(Matrix::new(rows))
     }
 
-    pub fn sum(&self) -> f32 {
+    pub fn sum(&self) -> T {
         self.rows.iter().map(Vector::sum).sum()
     }
 
-    pub fn sum_cols(&self) -> Vector {
+    pub fn sum_cols(&self) -> Vector<T> {
         let col_count = self.col_count();
-        let mut result = vec![0.0; col_count];
+        let mut result = vec![T::zero(); col_count];
         
         for row in &self.rows {
             for (i, &e) in row.elements.iter().enumerate() {
@@ -245,44 +358,27 @@ impl Matrix {
         Vector::new(result)
     }
 
-    pub fn sum_rows(&self) -> Vector {
+    pub fn sum_rows(&self) -> Vector<T> {
         Vector::new(self.rows.iter().map(Vector::sum).collect())
     }
 
-    pub fn mean(&self) -> f32 {
-        self.sum() / (self.row_count() * self.col_count()) as f32
+    pub fn mean(&self) -> T {
+        self.sum() / T::constant((self.row_count() * self.col_count()) as f64)
     }
 
-    pub fn mean_cols(&self) -> Vector {
-        self.sum_cols().scalar_multiply(1.0 / self.row_count() as f32)
+    pub fn mean_cols(&self) -> Vector<T> {
+        self.sum_cols().scalar_multiply(T::one() / T::constant(self.row_count() as f64))
     }
 
-    pub fn mean_rows(&self) -> Vector {
-        self.sum_rows().scalar_multiply(1.0 / self.col_count() as f32)
+    pub fn mean_rows(&self) -> Vector<T> {
+        self.sum_rows().scalar_multiply(T::one() / T::constant(self.col_count() as f64))
     }
 
-    pub fn to_string(&self) -> String {
-        serde_json::to_string(self).expect("Serialization failed")
-    }
-
-    pub fn from_string(s: &str) -> Result<Matrix, serde_json::Error> {
-        serde_json::from_str(s)
-    }
-
-    pub fn save(&self, path: &str) -> Result<(), std::io::Error> {
-        fs::write(path, self.to_string())
-    }
-
-    pub fn load(path: &str) -> Result<Matrix, std::io::Error> {
-        let contents = fs::read_to_string(path)?;
-        Matrix::from_string(&contents).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
-    }
-
-    pub fn iter(&self) -> Iter<Vector> {
+    pub fn iter(&self) -> Iter<'_, Vector<T>> {
         self.rows.iter()
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<Vector> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, Vector<T>> {
         self.rows.iter_mut()
     }
 
@@ -290,47 +386,217 @@ impl Matrix {
         self.rows.len()
     }
 
-    pub fn copy(&self) -> Matrix {
+    pub fn copy(&self) -> Matrix<T> {
         Matrix::new(self.rows.iter().map(Vector::copy).collect())
     }
 
-    pub fn dot(&self, other: &Vector) -> Result<f32, &'static str> {
+    pub fn dot(&self, other: &Vector<T>) -> Result<T, &'static str> {
         if self.col_count() != other.len() {
             return Err("Matrix and vector dimensions do not match for dot product");
         }
 
-        Ok(self.rows.iter().map(|row| row.dot(other)).sum())
+        Ok(self.rows.iter().map(|row| row.dot(other).unwrap()).sum())
     }
 
-    pub fn dot_with_matrix(&self, other: &Matrix) -> Result<f32, &'static str> {
+    pub fn dot_with_matrix(&self, other: &Matrix<T>) -> Result<T, &'static str> {
         if self.row_count() != other.row_count() || self.col_count() != other.col_count() {
             return Err("Matrix dimensions do not match for dot product");
         }
 
-        Ok(self.rows.iter().zip(&other.rows).map(|(row, other_row)| row.dot(other_row)).sum())
+        Ok(self.rows.iter().zip(&other.rows).map(|(row, other_row)| row.dot(other_row).unwrap()).sum())
     }
 
     pub fn shape(&self) -> (usize, usize) {
         (self.row_count(), self.col_count())
     }
 
+    /// Returns whether both matrices have the same shape and every pair of
+    /// elements differs by at most `tol`.
+    pub fn approx_eq(&self, other: &Matrix<T>, tol: T) -> bool {
+        self.row_count() == other.row_count() && self.rows.iter().zip(&other.rows).all(|(a, b)| a.approx_eq(b, tol))
+    }
+
     pub fn is_empty(&self) -> bool {
         self.rows.is_empty()
     }
 
-    pub fn rows(&self) -> &[Vector] {
+    pub fn rows(&self) -> &[Vector<T>] {
         &self.rows
     }
 
-    pub fn cols(&self) -> Vec<Vector> {
+    pub fn cols(&self) -> Vec<Vector<T>> {
         (0..self.col_count()).map(|i| self.get_col(i).unwrap()).collect()
     }
 
-    pub fn zeros(row_count: usize, col_count: usize) -> Matrix {
+    pub fn zeros(row_count: usize, col_count: usize) -> Matrix<T> {
         Matrix::new(vec![Vector::zeros(col_count); row_count])
     }
 
-    pub fn ones(row_count: usize, col_count: usize) -> Matrix {
+    pub fn ones(row_count: usize, col_count: usize) -> Matrix<T> {
         Matrix::new(vec![Vector::ones(col_count); row_count])
     }
+
+    /// Creates the `n x n` identity matrix.
+    pub fn identity(n: usize) -> Matrix<T> {
+        let mut identity = Matrix::zeros(n, n);
+        for (i, row) in identity.rows.iter_mut().enumerate() {
+            row.elements[i] = T::one();
+        }
+        identity
+    }
+
+    /// Returns the largest absolute value of any element, zero for an empty
+    /// matrix.
+    pub fn max_abs(&self) -> T {
+        self.rows.iter().flat_map(|row| &row.elements).fold(T::zero(), |max, &x| max.max(x.abs()))
+    }
+
+    /// Estimates the spectral norm, the largest singular value, by `iters`
+    /// steps of power iteration on `Wᵀ W` from the vector `(1, 2, …, n)`.
+    /// If that start lies in the null space, so the iteration collapses to
+    /// zero, it restarts from each basis vector in turn. The estimate never
+    /// exceeds the true norm, and approaches it from below unless the start
+    /// is exactly orthogonal to the top right-singular vector.
+    pub fn spectral_norm_estimate(&self, iters: usize) -> T {
+        let n = self.col_count();
+        let ramp = Vector::new((1..=n).map(|i| T::constant(i as f64)).collect());
+        let basis = (0..n).map(|k| Vector::new((0..n).map(|i| if i == k { T::one() } else { T::zero() }).collect()));
+        let mut estimate = T::zero();
+        for start in core::iter::once(ramp).chain(basis) {
+            estimate = self.power_iteration(start.normalize(), iters);
+            if estimate > T::zero() {
+                break;
+            }
+        }
+        estimate
+    }
+
+    /// Runs `iters` steps of power iteration on `Wᵀ W` from the unit vector
+    /// `v` and returns `|W v|` for the final `v`.
+    fn power_iteration(&self, mut v: Vector<T>, iters: usize) -> T {
+        let transposed = self.transpose();
+        for _ in 0..iters {
+            let image = self.multiply_with_vector(&v).unwrap();
+            v = transposed.multiply_with_vector(&image).unwrap().normalize();
+        }
+        self.multiply_with_vector(&v).unwrap().magnitude()
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.row_count() == self.col_count()
+    }
+
+    /// Returns the determinant of a square matrix, computed by Gaussian
+    /// elimination with partial pivoting.
+    pub fn determinant(&self) -> Result<T, String> {
+        if !self.is_square() {
+            return Err(format!("Determinant requires a square matrix, got {}x{}", self.row_count(), self.col_count()));
+        }
+
+        let mut a = self.to_vec();
+        let n = a.len();
+        let mut determinant = T::one();
+        for col in 0..n {
+            let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap_or(Ordering::Equal)).unwrap();
+            if a[pivot][col] == T::zero() {
+                return Ok(T::zero());
+            }
+            if pivot != col {
+                a.swap(col, pivot);
+                determinant = -determinant;
+            }
+            determinant *= a[col][col];
+
+            let pivot_row = a[col].clone();
+            for row in &mut a[col + 1..] {
+                let factor = row[col] / pivot_row[col];
+                row.iter_mut().zip(&pivot_row).for_each(|(e, p)| *e -= factor * *p);
+            }
+        }
+        Ok(determinant)
+    }
+
+    /// Returns the inverse of a square matrix, computed by Gauss–Jordan
+    /// elimination with partial pivoting. Fails for non-square and singular
+    /// matrices, where a pivot counts as zero relative to the largest entry,
+    /// within `epsilon * n * max_abs`, so scaling a matrix does not change
+    /// whether it is invertible.
+    pub fn inverse(&self) -> Result<Matrix<T>, String> {
+        if !self.is_square() {
+            return Err(format!("Inverse requires a square matrix, got {}x{}", self.row_count(), self.col_count()));
+        }
+
+        let n = self.row_count();
+        let tolerance = T::epsilon() * T::constant(n as f64) * self.max_abs();
+        let mut a = self.to_vec();
+        let mut inverse = Matrix::identity(n).to_vec();
+        for col in 0..n {
+            let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap_or(Ordering::Equal)).unwrap();
+            if a[pivot][col].abs() <= tolerance {
+                return Err("Matrix is singular and cannot be inverted".to_string());
+            }
+            a.swap(col, pivot);
+            inverse.swap(col, pivot);
+
+            let scale = T::one() / a[col][col];
+            a[col].iter_mut().for_each(|e| *e *= scale);
+            inverse[col].iter_mut().for_each(|e| *e *= scale);
+
+            let (pivot_row, pivot_inverse) = (a[col].clone(), inverse[col].clone());
+            for row in (0..n).filter(|&row| row != col) {
+                let factor = a[row][col];
+                a[row].iter_mut().zip(&pivot_row).for_each(|(e, p)| *e -= factor * *p);
+                inverse[row].iter_mut().zip(&pivot_inverse).for_each(|(e, p)| *e -= factor * *p);
+            }
+        }
+        Ok(Matrix::from_vec(inverse))
+    }
+}
+
+impl Matrix {
+    #[cfg(feature = "std")]
+    pub fn random(row_count: usize, col_count: usize) -> Matrix {
+        Matrix::random_with_rng(row_count, col_count, &mut rand::thread_rng())
+    }
+
+    /// Creates a matrix with elements drawn uniformly from `[-1, 1)` using `rng`.
+    pub fn random_with_rng(row_count: usize, col_count: usize, rng: &mut impl rand::Rng) -> Matrix {
+        let rows: Vec<Vector> = (0..row_count).map(|_| Vector::random_with_rng(col_count, rng)).collect();
+        Matrix::new(rows)
+    }
+
+    /// Creates a matrix with elements drawn from a zero-mean Gaussian with the
+    /// given standard deviation.
+    #[cfg(feature = "std")]
+    pub fn random_normal(row_count: usize, col_count: usize, std_dev: f32) -> Matrix {
+        let mut rng = rand::thread_rng();
+        let normal = Normal::new(0.0, std_dev).expect("Standard deviation must be finite and non-negative");
+        let rows: Vec<Vector> = (0..row_count)
+            .map(|_| Vector::new((0..col_count).map(|_| normal.sample(&mut rng)).collect()))
+            .collect();
+        Matrix::new(rows)
+    }
+
+    /// Serializes for persistence; use `Display` for human-readable output.
+    #[cfg(feature = "std")]
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        serde_json::to_string(self).expect("Serialization failed")
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_string(s: &str) -> Result<Matrix, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: &str) -> Result<(), std::io::Error> {
+        fs::write(path, self.to_string())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn load(path: &str) -> Result<Matrix, std::io::Error> {
+        let contents = fs::read_to_string(path)?;
+        Matrix::from_string(&contents).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
 }
@@ -1,7 +1,15 @@
+pub mod float;
 pub mod vector;
 pub mod matrix;
 pub mod layer;
+pub mod spline;
+pub mod batch_norm;
+pub mod symbolic;
 
+pub use float::Float;
 pub use vector::Vector;
 pub use matrix::Matrix;
-pub use layer::Layer;
\ No newline at end of file
+pub use layer::{Layer, SplineGradients};
+pub use spline::Spline;
+pub use batch_norm::BatchNorm;
+pub use symbolic::{SymbolicFit, SymbolicFn};
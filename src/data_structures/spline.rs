@@ -0,0 +1,255 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use rand::Rng;
+use crate::data_structures::symbolic::{fit_best, SymbolicFn};
+use crate::data_structures::Float;
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float as _;
+
+/// A univariate B-spline `φ(x) = Σ c_i B_{i,k}(x)`, the learnable function
+/// placed on every edge of a Kolmogorov–Arnold layer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spline<T = f32> {
+    /// The non-decreasing knot vector, with `control_points.len() + degree + 1` entries.
+    pub knots: Vec<T>,
+    /// The coefficient of each basis function.
+    pub control_points: Vec<T>,
+    /// The polynomial degree `k` of the basis functions.
+    pub degree: usize,
+}
+
+impl<T: Float> Spline<T> {
+    /// Creates a spline from an explicit knot vector and control points.
+    pub fn from_knots(knots: Vec<T>, control_points: Vec<T>, degree: usize) -> Result<Self, String> {
+        if knots.len() != control_points.len() + degree + 1 {
+            return Err(format!(
+                "A degree {} spline with {} control points needs {} knots, got {}",
+                degree,
+                control_points.len(),
+                control_points.len() + degree + 1,
+                knots.len()
+            ));
+        }
+
+        if knots.windows(2).any(|w| w[0] > w[1]) {
+            return Err("Spline knots must be non-decreasing".to_string());
+        }
+
+        Ok(Spline { knots, control_points, degree })
+    }
+
+    /// Returns the interval `[t_k, t_n]` on which the basis functions sum to one.
+    pub fn domain(&self) -> (T, T) {
+        (self.knots[self.degree], self.knots[self.control_points.len()])
+    }
+
+    /// Evaluates the basis function `B_{i,k}(x)` using the Cox–de Boor recursion.
+    pub fn basis(&self, i: usize, k: usize, x: T) -> T {
+        if k == 0 {
+            return if self.knots[i] <= x && x < self.knots[i + 1] { T::one() } else { T::zero() };
+        }
+
+        let mut value = T::zero();
+        let left = self.knots[i + k] - self.knots[i];
+        if left > T::zero() {
+            value += (x - self.knots[i]) / left * self.basis(i, k - 1, x);
+        }
+        let right = self.knots[i + k + 1] - self.knots[i + 1];
+        if right > T::zero() {
+            value += (self.knots[i + k + 1] - x) / right * self.basis(i + 1, k - 1, x);
+        }
+        value
+    }
+
+    /// Evaluates every degree-`k` basis function at `x`, one per control point.
+    pub fn basis_values(&self, x: T) -> Vec<T> {
+        (0..self.control_points.len()).map(|i| self.basis(i, self.degree, x)).collect()
+    }
+
+    /// Evaluates the spline at `x`.
+    pub fn evaluate(&self, x: T) -> T {
+        self.basis_values(x).iter().zip(&self.control_points).map(|(&b, &c)| b * c).sum()
+    }
+
+    /// Evaluates the derivative `φ'(x)` of the spline.
+    pub fn derivative(&self, x: T) -> T {
+        let k = self.degree;
+        if k == 0 {
+            return T::zero();
+        }
+
+        let mut value = T::zero();
+        for (i, c) in self.control_points.iter().enumerate() {
+            let left = self.knots[i + k] - self.knots[i];
+            if left > T::zero() {
+                value += *c * T::constant(k as f64) * self.basis(i, k - 1, x) / left;
+            }
+            let right = self.knots[i + k + 1] - self.knots[i + 1];
+            if right > T::zero() {
+                value -= *c * T::constant(k as f64) * self.basis(i + 1, k - 1, x) / right;
+            }
+        }
+        value
+    }
+}
+
+impl Spline {
+    /// Creates a spline with `grid_size` uniform intervals over `[min, max]` and
+    /// all control points set to zero.
+    ///
+    /// The grid is extended by `degree` knots on each side so that every point
+    /// of `[min, max]` is covered by a full set of basis functions.
+    pub fn uniform(grid_size: usize, degree: usize, min: f32, max: f32) -> Self {
+        let step = (max - min) / grid_size as f32;
+        let knots = (0..grid_size + 2 * degree + 1)
+            .map(|i| min + (i as f32 - degree as f32) * step)
+            .collect();
+        Spline {
+            knots,
+            control_points: vec![0.0; grid_size + degree],
+            degree,
+        }
+    }
+
+    /// Creates a spline of the given degree with `grid_size` uniform intervals
+    /// over `[-1, 1]`, the default KAN input range, and zero control points.
+    pub fn new(grid_size: usize, degree: usize) -> Self {
+        Spline::uniform(grid_size, degree, -1.0, 1.0)
+    }
+
+    /// Creates a uniform spline over `[min, max]` with small random control points.
+    #[cfg(feature = "std")]
+    pub fn random(grid_size: usize, degree: usize, min: f32, max: f32) -> Self {
+        let mut rng = rand::thread_rng();
+        let mut spline = Spline::uniform(grid_size, degree, min, max);
+        spline.control_points.iter_mut().for_each(|c| *c = rng.gen_range(-0.1..0.1));
+        spline
+    }
+
+    /// Fits a spline with `grid_size` uniform intervals over `[min, max]` to
+    /// `f`, by least-squares projection of `f` onto the spline basis sampled
+    /// densely over the interval.
+    pub fn fit(grid_size: usize, degree: usize, min: f32, max: f32, f: impl Fn(f32) -> f32) -> Self {
+        let mut spline = Spline::uniform(grid_size, degree, min, max);
+        let n = spline.control_points.len();
+        let samples = 4 * (grid_size + degree) + 1;
+
+        let mut gram = vec![vec![0.0; n]; n];
+        let mut rhs = vec![0.0; n];
+        for s in 0..samples {
+            let x = min + (max - min) * s as f32 / (samples - 1) as f32;
+            let y = f(x);
+            let basis = spline.basis_values(x);
+            for i in 0..n {
+                rhs[i] += basis[i] * y;
+                for j in 0..n {
+                    gram[i][j] += basis[i] * basis[j];
+                }
+            }
+        }
+
+        spline.control_points = solve_linear_system(gram, rhs);
+        spline
+    }
+
+    /// Re-fits the spline onto a uniform grid of `new_grid_size` intervals over
+    /// the same domain, keeping its degree.
+    ///
+    /// The new control points are the least-squares projection of the current
+    /// function onto the new basis, so a finer grid reproduces the current
+    /// shape and adds resolution for further training.
+    pub fn refine_grid(&mut self, new_grid_size: usize) {
+        let (min, max) = self.domain();
+        *self = Spline::fit(new_grid_size, self.degree, min, max, |x| self.evaluate(x));
+    }
+
+    /// Widens the domain to cover `[new_min, new_max]` by adding knots beyond
+    /// each end, spaced like the outermost existing intervals, with one new
+    /// control point per knot.
+    ///
+    /// The added basis functions vanish on the old domain, so the spline is
+    /// unchanged there. The new control points continue the control polygon
+    /// linearly, so the spline extends continuously, and exactly for a linear
+    /// function.
+    ///
+    /// Panics unless the spline has more control points than its degree, that
+    /// is, at least one grid interval.
+    pub fn extend_grid(&mut self, new_min: f32, new_max: f32) {
+        let n = self.control_points.len();
+        assert!(
+            n > self.degree,
+            "Cannot extend the grid of a degree {} spline with {} control points; it needs at least {}",
+            self.degree,
+            n,
+            self.degree + 1
+        );
+        let (min, max) = self.domain();
+        let fallback = (max - min) / (n - self.degree).max(1) as f32;
+        let spacing = |a: f32, b: f32| if b > a { b - a } else { fallback };
+        let (left_step, right_step) = (spacing(self.knots[0], self.knots[1]), spacing(self.knots[n + self.degree - 1], self.knots[n + self.degree]));
+        let extra = |gap: f32, step: f32| if gap > 0.0 && step > 0.0 { (gap / step).ceil() as usize } else { 0 };
+        let (left, right) = (extra(min - new_min, left_step), extra(new_max - max, right_step));
+
+        let slope = |a: f32, b: f32| if n > 1 { b - a } else { 0.0 };
+        let (first, second) = (self.control_points[0], self.control_points[n.min(2) - 1]);
+        let (last, before_last) = (self.control_points[n - 1], self.control_points[n.saturating_sub(2)]);
+        let (left_slope, right_slope) = (slope(first, second), slope(before_last, last));
+
+        let mut knots: Vec<f32> = (1..=left).rev().map(|m| self.knots[0] - m as f32 * left_step).collect();
+        knots.extend(&self.knots);
+        knots.extend((1..=right).map(|m| self.knots[n + self.degree] + m as f32 * right_step));
+        let mut control_points: Vec<f32> = (1..=left).rev().map(|m| first - m as f32 * left_slope).collect();
+        control_points.extend(&self.control_points);
+        control_points.extend((1..=right).map(|m| last + m as f32 * right_slope));
+
+        self.knots = knots;
+        self.control_points = control_points;
+    }
+
+    /// Finds the candidate `f` for which `a f(x) + b` best fits the spline over
+    /// its domain, returning it with the R² of the fit.
+    pub fn fit_symbolic(&self, candidates: &[SymbolicFn]) -> Option<(SymbolicFn, f32)> {
+        let (min, max) = self.domain();
+        let xs = sample_points(min, max);
+        let ys: Vec<f32> = xs.iter().map(|&x| self.evaluate(x)).collect();
+        fit_best(&xs, &ys, candidates).map(|fit| (fit.function, fit.r2))
+    }
+}
+
+/// The points at which functions are sampled for symbolic fitting.
+pub(crate) fn sample_points(min: f32, max: f32) -> Vec<f32> {
+    const SAMPLES: usize = 101;
+    (0..SAMPLES).map(|s| min + (max - min) * s as f32 / (SAMPLES - 1) as f32).collect()
+}
+
+/// Solves `a x = b` by Gaussian elimination with partial pivoting.
+fn solve_linear_system(mut a: Vec<Vec<f32>>, mut b: Vec<f32>) -> Vec<f32> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        if a[col][col] == 0.0 {
+            continue;
+        }
+
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            for (e, p) in a[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *e -= factor * p;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f32 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = if a[row][row] == 0.0 { 0.0 } else { (b[row] - sum) / a[row][row] };
+    }
+    x
+}
@@ -0,0 +1,116 @@
+use crate::prelude::*;
+use core::fmt;
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float as _;
+
+/// A candidate elementary function for replacing a learned edge with a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolicFn {
+    Identity,
+    Square,
+    Cube,
+    Sin,
+    Cos,
+    Tanh,
+    Exp,
+    /// The natural logarithm of `|x|`.
+    Log,
+    Abs,
+}
+
+impl SymbolicFn {
+    /// Every candidate function.
+    pub const ALL: [SymbolicFn; 9] = [
+        SymbolicFn::Identity,
+        SymbolicFn::Square,
+        SymbolicFn::Cube,
+        SymbolicFn::Sin,
+        SymbolicFn::Cos,
+        SymbolicFn::Tanh,
+        SymbolicFn::Exp,
+        SymbolicFn::Log,
+        SymbolicFn::Abs,
+    ];
+
+    pub fn evaluate(&self, x: f32) -> f32 {
+        match self {
+            SymbolicFn::Identity => x,
+            SymbolicFn::Square => x * x,
+            SymbolicFn::Cube => x * x * x,
+            SymbolicFn::Sin => x.sin(),
+            SymbolicFn::Cos => x.cos(),
+            SymbolicFn::Tanh => x.tanh(),
+            SymbolicFn::Exp => x.exp(),
+            SymbolicFn::Log => x.abs().ln(),
+            SymbolicFn::Abs => x.abs(),
+        }
+    }
+
+    /// Writes the function applied to the variable `var`, e.g. `sin(x0)`.
+    pub fn formula(&self, var: &str) -> String {
+        match self {
+            SymbolicFn::Identity => var.to_string(),
+            SymbolicFn::Square => format!("{}^2", var),
+            SymbolicFn::Cube => format!("{}^3", var),
+            SymbolicFn::Sin => format!("sin({})", var),
+            SymbolicFn::Cos => format!("cos({})", var),
+            SymbolicFn::Tanh => format!("tanh({})", var),
+            SymbolicFn::Exp => format!("exp({})", var),
+            SymbolicFn::Log => format!("log(|{}|)", var),
+            SymbolicFn::Abs => format!("|{}|", var),
+        }
+    }
+}
+
+impl fmt::Display for SymbolicFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.formula("x"))
+    }
+}
+
+/// The least-squares fit `y ≈ scale * function(x) + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolicFit {
+    pub function: SymbolicFn,
+    pub scale: f32,
+    pub offset: f32,
+    /// The coefficient of determination of the fit.
+    pub r2: f32,
+}
+
+impl SymbolicFit {
+    /// Writes the fitted formula in the variable `var`, e.g. `1.0000 * sin(x0) + 0.0000`.
+    pub fn formula(&self, var: &str) -> String {
+        format!("{:.4} * {} + {:.4}", self.scale, self.function.formula(var), self.offset)
+    }
+}
+
+/// Fits `y ≈ a f(x) + b` for every candidate `f` and returns the fit with the
+/// highest R². Candidates that are constant or undefined on the samples are
+/// skipped.
+pub(crate) fn fit_best(xs: &[f32], ys: &[f32], candidates: &[SymbolicFn]) -> Option<SymbolicFit> {
+    let n = ys.len() as f32;
+    let y_mean = ys.iter().sum::<f32>() / n;
+    let total: f32 = ys.iter().map(|y| (y - y_mean).powi(2)).sum();
+
+    candidates
+        .iter()
+        .filter_map(|&function| {
+            let fs: Vec<f32> = xs.iter().map(|&x| function.evaluate(x)).collect();
+            if fs.iter().any(|f| !f.is_finite()) {
+                return None;
+            }
+            let f_mean = fs.iter().sum::<f32>() / n;
+            let variance: f32 = fs.iter().map(|f| (f - f_mean).powi(2)).sum();
+            if variance == 0.0 {
+                return None;
+            }
+            let covariance: f32 = fs.iter().zip(ys).map(|(f, y)| (f - f_mean) * (y - y_mean)).sum();
+            let scale = covariance / variance;
+            let offset = y_mean - scale * f_mean;
+            let residual: f32 = fs.iter().zip(ys).map(|(f, y)| (y - scale * f - offset).powi(2)).sum();
+            let r2 = if total == 0.0 { 1.0 } else { 1.0 - residual / total };
+            Some(SymbolicFit { function, scale, offset, r2 })
+        })
+        .max_by(|a, b| a.r2.total_cmp(&b.r2))
+}
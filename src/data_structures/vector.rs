@@ -1,37 +1,70 @@
-use std::ops::{MulAssign, Sub};
-use crate::data_structures::Matrix;
+use crate::prelude::*;
+use core::fmt;
+use core::ops::{MulAssign, Sub};
+use crate::data_structures::{Float, Matrix};
 use serde::{Deserialize, Serialize};
 use rand::Rng;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Vector {
-    pub elements: Vec<f32>,
+/// A dense vector of `T` elements, `f32` unless stated otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vector<T = f32> {
+    pub elements: Vec<T>,
 }
 
-impl Sub for Vector {
-    type Output = Vector;
+impl<T: Float> fmt::Display for Vector<T> {
+    /// Formats the elements comma-separated in brackets, honouring any
+    /// requested precision, e.g. `[1.00, -2.50]` for `{:.2}`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, x) in self.elements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match f.precision() {
+                Some(precision) => write!(f, "{:.*}", precision, x)?,
+                None => write!(f, "{}", x)?,
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T: Float> Sub for Vector<T> {
+    type Output = Vector<T>;
 
-    fn sub(self, other: Vector) -> Vector {
+    fn sub(self, other: Vector<T>) -> Vector<T> {
         Vector::new(
-            self.elements.iter().zip(other.elements).map(|(a, b)| a - b).collect()
+            self.elements.iter().zip(other.elements).map(|(&a, b)| a - b).collect()
         )
     }
 }
 
-impl MulAssign<f32> for Vector {
-    fn mul_assign(&mut self, scalar: f32) {
+impl<T: Float> MulAssign<T> for Vector<T> {
+    fn mul_assign(&mut self, scalar: T) {
         self.elements.iter_mut().for_each(|x| *x *= scalar);
     }
 }
 
-impl MulAssign for Vector {
-    fn mul_assign(&mut self, other: Vector) {
+impl<T: Float> MulAssign for Vector<T> {
+    fn mul_assign(&mut self, other: Vector<T>) {
         self.elements.iter_mut().zip(other.elements).for_each(|(a, b)| *a *= b);
     }
 }
 
-impl Vector {
-    pub fn new(elements: Vec<f32>) -> Self {
+impl<T> From<Vec<T>> for Vector<T> {
+    fn from(elements: Vec<T>) -> Self {
+        Vector { elements }
+    }
+}
+
+impl<T> From<Vector<T>> for Vec<T> {
+    fn from(vector: Vector<T>) -> Self {
+        vector.elements
+    }
+}
+
+impl<T: Float> Vector<T> {
+    pub fn new(elements: Vec<T>) -> Self {
         Self { elements }
     }
 
@@ -39,14 +72,20 @@ impl Vector {
         self.elements.len()
     }
 
-    pub fn dot(&self, other: &Vector) -> f32 {
+    /// Computes the dot product `Σ a_i b_i`, returning an error if the vectors
+    /// have different lengths.
+    pub fn dot(&self, other: &Vector<T>) -> Result<T, String> {
         if self.len() != other.len() {
-            panic!("Vectors must have the same length for dot product");
+            return Err(format!(
+                "Vectors must have the same length for dot product: {} and {}",
+                self.len(),
+                other.len()
+            ));
         }
-        self.elements.iter().zip(&other.elements).map(|(a, b)| a * b).sum()
+        Ok(self.elements.iter().zip(&other.elements).map(|(&a, &b)| a * b).sum())
     }
 
-    pub fn cross(&self, other: &Vector) -> Vector {
+    pub fn cross(&self, other: &Vector<T>) -> Vector<T> {
         // Write a general cross product for vectors of any size
         if self.len() != other.len() {
             panic!("Vectors must have the same length for cross product");
@@ -60,7 +99,7 @@ impl Vector {
     }
 
 
-    pub fn outer_product(&self, other: &Self) -> Matrix {
+    pub fn outer_product(&self, other: &Self) -> Matrix<T> {
         let mut matrix = Matrix::new(vec![]);
         for i in 0..self.len() {
             let mut row = vec![];
@@ -72,143 +111,210 @@ impl Vector {
         matrix
     }
 
-    pub fn magnitude(&self) -> f32 {
-        self.elements.iter().map(|&x| x.powi(2)).sum::<f32>().sqrt()
+    /// Returns the Euclidean norm, equal to `self.dot(self)?.sqrt()`.
+    pub fn magnitude(&self) -> T {
+        self.elements.iter().map(|&x| x.powi(2)).sum::<T>().sqrt()
+    }
+
+    /// Computes the cosine of the angle between the vectors,
+    /// `a · b / (|a| |b|)`, returning an error if their lengths differ or
+    /// either has zero magnitude.
+    pub fn cosine_similarity(&self, other: &Vector<T>) -> Result<T, String> {
+        let dot = self.dot(other)?;
+        let magnitudes = self.magnitude() * other.magnitude();
+        if magnitudes == T::zero() {
+            return Err("Cosine similarity is undefined for a zero vector".to_string());
+        }
+        Ok(dot / magnitudes)
     }
 
-    pub fn normalize(&self) -> Vector {
+    /// Scales the vector to unit magnitude. A zero vector is returned as-is
+    /// instead of producing NaNs.
+    pub fn normalize(&self) -> Vector<T> {
         let magnitude = self.magnitude();
+        if magnitude == T::zero() {
+            return Vector::zeros(self.len());
+        }
         Vector::new(self.elements.iter().map(|&x| x / magnitude).collect())
     }
 
-    pub fn distance(&self, other: &Vector) -> f32 {
+    /// Rescales the elements linearly onto `[0, 1]`, mapping the minimum to 0
+    /// and the maximum to 1. A constant vector maps to zeros.
+    pub fn scale_to_unit(&self) -> Vector<T> {
+        let min = self.elements.iter().copied().fold(T::infinity(), T::min);
+        let max = self.elements.iter().copied().fold(T::neg_infinity(), T::max);
+        if max <= min {
+            return Vector::zeros(self.len());
+        }
+        self.map(|x| (x - min) / (max - min))
+    }
+
+    pub fn distance(&self, other: &Vector<T>) -> T {
         self.subtract(other).magnitude()
     }
 
-    pub fn angle(&self, other: &Vector) -> f32 {
-        let dot = self.dot(other);
+    pub fn angle(&self, other: &Vector<T>) -> T {
+        let dot = self.dot(other).unwrap();
         let magnitude = self.magnitude() * other.magnitude();
         (dot / magnitude).acos()
     }
 
-    pub fn project(&self, other: &Vector) -> Vector {
-        let dot = self.dot(other);
+    pub fn project(&self, other: &Vector<T>) -> Vector<T> {
+        let dot = self.dot(other).unwrap();
         let magnitude_sq = other.magnitude().powi(2);
         other.scalar_multiply(dot / magnitude_sq)
     }
 
-    pub fn reflect(&self, normal: &Vector) -> Vector {
-        let dot = self.dot(normal);
-        normal.scalar_multiply(2.0 * dot) - self.scalar_multiply(dot)
+    pub fn reflect(&self, normal: &Vector<T>) -> Vector<T> {
+        let dot = self.dot(normal).unwrap();
+        normal.scalar_multiply(T::constant(2.0) * dot) - self.scalar_multiply(dot)
     }
 
-    pub fn add(&self, other: &Vector) -> Vector {
-        Vector::new(
-            self.elements.iter().zip(&other.elements).map(|(a, b)| a + b).collect()
-        )
+    /// Adds `other` element-wise, failing if the lengths differ.
+    pub fn add(&self, other: &Vector<T>) -> Result<Vector<T>, String> {
+        if self.len() != other.len() {
+            return Err(format!("Vector lengths do not match for addition: {} and {}", self.len(), other.len()));
+        }
+        Ok(Vector::new(
+            self.elements.iter().zip(&other.elements).map(|(&a, &b)| a + b).collect()
+        ))
     }
 
-    pub fn add_scalar(&self, scalar: f32) -> Vector {
-        Vector::new(self.elements.iter().map(|a| a + scalar).collect())
+    pub fn add_scalar(&self, scalar: T) -> Vector<T> {
+        Vector::new(self.elements.iter().map(|&a| a + scalar).collect())
     }
 
-    pub fn scalar_multiply(&self, scalar: f32) -> Vector {
-        Vector::new(self.elements.iter().map(|a| a * scalar).collect())
+    pub fn scalar_multiply(&self, scalar: T) -> Vector<T> {
+        Vector::new(self.elements.iter().map(|&a| a * scalar).collect())
     }
 
-    pub fn subtract(&self, other: &Vector) -> Vector {
+    pub fn subtract(&self, other: &Vector<T>) -> Vector<T> {
         if self.len() != other.len() {
             panic!("Vectors must have the same length for subtraction");
         }
         Vector::new(
-            self.elements.iter().zip(&other.elements).map(|(a, b)| a - b).collect()
+            self.elements.iter().zip(&other.elements).map(|(&a, &b)| a - b).collect()
         )
     }
 
-    pub fn elementwise_multiply(&self, other: &Vector) -> Vector {
+    pub fn elementwise_multiply(&self, other: &Vector<T>) -> Vector<T> {
         // round  to 2 decimal places
         Vector::new(
-            self.elements.iter().zip(&other.elements).map(|(a, b)| (a * b)).collect()
+            self.elements.iter().zip(&other.elements).map(|(&a, &b)| a * b).collect()
         )
     }
 
-    pub fn elementwise_divide(&self, other: &Vector) -> Vector {
+    pub fn elementwise_divide(&self, other: &Vector<T>) -> Vector<T> {
         // Round to 2 decimal places
-        let elements = self.elements.iter().zip(&other.elements).map(|(a, b)| (a / b) * 10.0).map(|x| x.round() / 10.0).collect();
+        let elements = self.elements.iter().zip(&other.elements).map(|(a, b)| (*a / *b) * T::constant(10.0)).map(|x| x.round() / T::constant(10.0)).collect();
         Vector::new(elements)
     }
 
-    pub fn elementwise_sqrt(&self) -> Vector {
+    pub fn elementwise_sqrt(&self) -> Vector<T> {
         Vector::new(self.elements.iter().map(|&x| x.sqrt()).collect())
     }
 
-    pub fn sum(&self) -> f32 {
-        self.elements.iter().sum()
+    pub fn sum(&self) -> T {
+        self.elements.iter().copied().sum()
     }
 
-    pub fn mean(&self) -> f32 {
-        self.sum() / self.len() as f32
+    pub fn mean(&self) -> T {
+        self.sum() / T::constant(self.len() as f64)
     }
 
-    pub fn sigmoid(&self) -> Vector {
-        Vector::new(self.elements.iter().map(|&x| 1.0 / (1.0 + (-x).exp())).collect())
+    /// Returns the population standard deviation of the elements.
+    pub fn std(&self) -> T {
+        let mean = self.mean();
+        (self.elements.iter().map(|&x| (x - mean).powi(2)).sum::<T>() / T::constant(self.len() as f64)).sqrt()
     }
 
-    pub fn sigmoid_derivative(&self) -> Vector {
-        self.sigmoid().elementwise_multiply(&self.scalar_multiply(-1.0).add_scalar(1.0))
+    pub fn sigmoid(&self) -> Vector<T> {
+        Vector::new(self.elements.iter().map(|&x| T::one() / (T::one() + (-x).exp())).collect())
     }
 
-    pub fn relu(&self) -> Vector {
-        Vector::new(self.elements.iter().map(|&x| x.max(0.0)).collect())
+    pub fn sigmoid_derivative(&self) -> Vector<T> {
+        self.sigmoid().elementwise_multiply(&self.scalar_multiply(-T::one()).add_scalar(T::one()))
     }
 
-    pub fn relu_derivative(&self) -> Vector {
-        Vector::new(self.elements.iter().map(|&x| if x > 0.0 { 1.0 } else { 0.0 }).collect())
+    /// Computes the softmax of the vector, subtracting the maximum element
+    /// first for numerical stability.
+    pub fn softmax(&self) -> Vector<T> {
+        let max = self.elements.iter().copied().fold(T::neg_infinity(), T::max);
+        let exps = self.map(|x| (x - max).exp());
+        let sum = exps.sum();
+        exps.scalar_multiply(T::one() / sum)
     }
 
-    pub fn to_vec(&self) -> Vec<f32> {
+    pub fn relu(&self) -> Vector<T> {
+        Vector::new(self.elements.iter().map(|&x| x.max(T::zero())).collect())
+    }
+
+    pub fn relu_derivative(&self) -> Vector<T> {
+        Vector::new(self.elements.iter().map(|&x| if x > T::zero() { T::one() } else { T::zero() }).collect())
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
         self.elements.clone()
     }
 
-    pub fn from_vec(vec: Vec<f32>) -> Vector {
+    pub fn from_vec(vec: Vec<T>) -> Vector<T> {
         Self::new(vec)
     }
 
-    pub fn to_string(&self) -> String {
-        format!("{:?}", self.elements)
+    pub fn from_slice(s: &[T]) -> Vector<T> {
+        Self::new(s.to_vec())
     }
 
-    pub fn from_string(s: &str) -> Result<Vector, std::num::ParseFloatError> {
-        let elements: Result<Vec<f32>, _> = s.split(", ").map(str::parse).collect();
-        elements.map(Self::new)
+    /// Returns the elements of `self` followed by those of `other`.
+    pub fn concat(&self, other: &Vector<T>) -> Vector<T> {
+        Vector::new(self.elements.iter().chain(&other.elements).copied().collect())
     }
 
-    pub fn random(size: usize) -> Vector {
-        let mut rng = rand::thread_rng();
-        Vector::new((0..size).map(|_| rng.gen_range(-1.0..1.0)).collect())
+    /// Returns the elements in `start..end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start > end` or `end` is past the end of the vector.
+    pub fn slice(&self, start: usize, end: usize) -> Vector<T> {
+        if start > end || end > self.len() {
+            panic!("Slice range {}..{} out of bounds for vector of length {}", start, end, self.len());
+        }
+        Vector::from_slice(&self.elements[start..end])
+    }
+
+    /// Serializes for persistence; use `Display` for human-readable output.
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        format!("{:?}", self.elements)
     }
 
-    pub fn map(&self, f: impl Fn(f32) -> f32) -> Vector {
+    pub fn map(&self, f: impl Fn(T) -> T) -> Vector<T> {
         Vector::new(self.elements.iter().map(|&x| f(x)).collect())
     }
 
-    pub fn map_with_index(&self, f: impl Fn(f32, usize) -> f32) -> Vector {
+    /// Clamps every element into `[min, max]`. Panics if `min > max`.
+    pub fn clamp(&self, min: T, max: T) -> Vector<T> {
+        assert!(min <= max, "Clamp bounds must satisfy min <= max, got {} > {}", min, max);
+        self.map(|x| x.max(min).min(max))
+    }
+
+    pub fn map_with_index(&self, f: impl Fn(T, usize) -> T) -> Vector<T> {
         Vector::new(self.elements.iter().enumerate().map(|(i, &x)| f(x, i)).collect())
     }
 
-    pub fn map_with_vector(&self, other: &Vector, f: impl Fn(f32, f32) -> f32) -> Vector {
+    pub fn map_with_vector(&self, other: &Vector<T>, f: impl Fn(T, T) -> T) -> Vector<T> {
         Vector::new(
             self.elements.iter().zip(&other.elements).map(|(&a, &b)| f(a, b)).collect()
         )
     }
 
-    pub fn map_with_vector_index(&self, other: &Vector, f: impl Fn(f32, f32, usize) -> f32) -> Vector {
+    pub fn map_with_vector_index(&self, other: &Vector<T>, f: impl Fn(T, T, usize) -> T) -> Vector<T> {
         Vector::new(
             self.elements.iter().zip(&other.elements).enumerate().map(|(i, (&a, &b))| f(a, b, i)).collect()
         )
     }
 
-    pub fn unwrap(&self) -> Result<Vector, String> {
+    pub fn unwrap(&self) -> Result<Vector<T>, String> {
         if self.is_finite() {
             Ok(self.clone())
         } else {
@@ -224,8 +330,36 @@ impl Vector {
         self.elements.iter().all(|&x| x.is_finite())
     }
 
+    /// Returns the index of the largest element, the first one on ties, or 0
+    /// for an empty vector.
+    pub fn argmax(&self) -> usize {
+        self.first_index_where(|x, best| x > best)
+    }
+
+    /// Returns the index of the smallest element, the first one on ties, or 0
+    /// for an empty vector.
+    pub fn argmin(&self) -> usize {
+        self.first_index_where(|x, best| x < best)
+    }
+
+    fn first_index_where(&self, better: impl Fn(T, T) -> bool) -> usize {
+        let mut best = 0;
+        for (i, &x) in self.elements.iter().enumerate() {
+            if better(x, self.elements[best]) {
+                best = i;
+            }
+        }
+        best
+    }
+
+    /// Returns whether both vectors have the same length and every pair of
+    /// elements differs by at most `tol`.
+    pub fn approx_eq(&self, other: &Vector<T>, tol: T) -> bool {
+        self.len() == other.len() && self.elements.iter().zip(&other.elements).all(|(&a, &b)| (a - b).abs() <= tol)
+    }
+
     pub fn is_zero(&self) -> bool {
-        self.elements.iter().all(|&x| x.abs() < f32::EPSILON)
+        self.elements.iter().all(|&x| x.abs() < T::epsilon())
     }
 
     pub fn is_nan(&self) -> bool {
@@ -237,54 +371,78 @@ impl Vector {
     }
 
     pub fn is_positive(&self) -> bool {
-        self.elements.iter().all(|&x| x > 0.0)
+        self.elements.iter().all(|&x| x > T::zero())
     }
 
     pub fn is_negative(&self) -> bool {
-        self.elements.iter().all(|&x| x < 0.0)
+        self.elements.iter().all(|&x| x < T::zero())
     }
 
     pub fn is_nonpositive(&self) -> bool {
-        self.elements.iter().all(|&x| x <= 0.0)
+        self.elements.iter().all(|&x| x <= T::zero())
     }
 
     pub fn is_nonnegative(&self) -> bool {
-        self.elements.iter().all(|&x| x >= 0.0)
+        self.elements.iter().all(|&x| x >= T::zero())
     }
 
     pub fn is_sorted(&self) -> bool {
         self.elements.windows(2).all(|w| w[0] <= w[1])
     }
 
-    pub fn set_elements(&mut self, elements: Vec<f32>) {
+    pub fn set_elements(&mut self, elements: Vec<T>) {
         self.elements = elements;
     }
 
-    pub fn set_element(&mut self, index: usize, element: f32) {
+    pub fn set_element(&mut self, index: usize, element: T) {
         self.elements[index] = element;
     }
 
-    pub fn get_element(&self, index: usize) -> f32 {
+    pub fn get_element(&self, index: usize) -> T {
         self.elements[index]
     }
 
-    pub fn get_elements(&self) -> Vec<f32> {
+    pub fn get_elements(&self) -> Vec<T> {
         self.elements.clone()
     }
 
-    pub fn get_elements_mut(&mut self) -> &mut Vec<f32> {
+    pub fn get_elements_mut(&mut self) -> &mut Vec<T> {
         &mut self.elements
     }
 
-    pub fn copy(&self) -> Vector {
+    pub fn copy(&self) -> Vector<T> {
         Vector::new(self.elements.clone())
     }
 
-    pub fn zeros(size: usize) -> Vector {
-        Vector::new(vec![0.0; size])
+    pub fn zeros(size: usize) -> Vector<T> {
+        Vector::new(vec![T::zero(); size])
+    }
+
+    pub fn ones(size: usize) -> Vector<T> {
+        Vector::new(vec![T::one(); size])
     }
+}
 
-    pub fn ones(size: usize) -> Vector {
-        Vector::new(vec![1.0; size])
+impl Vector {
+    /// Parses the `to_string` format, `[1.0, 2.0]`. The brackets are optional.
+    pub fn from_string(s: &str) -> Result<Vector, core::num::ParseFloatError> {
+        let s = s.trim();
+        let s = s.strip_prefix('[').unwrap_or(s);
+        let s = s.strip_suffix(']').unwrap_or(s).trim();
+        if s.is_empty() {
+            return Ok(Vector::new(Vec::new()));
+        }
+        let elements: Result<Vec<f32>, _> = s.split(',').map(|x| x.trim().parse()).collect();
+        elements.map(Self::new)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn random(size: usize) -> Vector {
+        Vector::random_with_rng(size, &mut rand::thread_rng())
+    }
+
+    /// Creates a vector with elements drawn uniformly from `[-1, 1)` using `rng`.
+    pub fn random_with_rng(size: usize, rng: &mut impl Rng) -> Vector {
+        Vector::new((0..size).map(|_| rng.gen_range(-1.0..1.0)).collect())
     }
 }
@@ -1,6 +1,14 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod prelude;
+
 pub mod data_structures;
 pub mod network;
 pub mod utils;
+#[cfg(feature = "std")]
+pub mod data;
 
 #[cfg(test)]
 pub mod tests;
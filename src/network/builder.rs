@@ -0,0 +1,84 @@
+use crate::data_structures::Layer;
+use crate::network::Network;
+use crate::utils::activations::Activation;
+
+/// How the weights of the layers created by a `NetworkBuilder` are initialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitScheme {
+    /// Uniform random weights and biases, as in `Layer::random`.
+    #[default]
+    Random,
+    /// Xavier (Glorot) normal weights and zero biases, as in `Layer::new_xavier`.
+    Xavier,
+    /// He normal weights and zero biases, as in `Layer::new_he`.
+    He,
+}
+
+/// Builds a `Network` layer by layer, checking that consecutive layers fit
+/// together.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NetworkBuilder {
+    shapes: Vec<(usize, usize, Activation)>,
+    init: InitScheme,
+}
+
+impl NetworkBuilder {
+    pub fn new() -> Self {
+        NetworkBuilder::default()
+    }
+
+    /// Appends a layer mapping `input_dim` inputs to `output_dim` outputs.
+    pub fn add_layer(mut self, input_dim: usize, output_dim: usize) -> Self {
+        self.shapes.push((input_dim, output_dim, Activation::Identity));
+        self
+    }
+
+    /// Sets the activation of the most recently added layer.
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        if let Some(shape) = self.shapes.last_mut() {
+            shape.2 = activation;
+        }
+        self
+    }
+
+    /// Sets the initialization scheme used for every layer.
+    pub fn init(mut self, init: InitScheme) -> Self {
+        self.init = init;
+        self
+    }
+
+    /// Builds the network, failing if it has no layers or if the output of a
+    /// layer does not match the input of the next.
+    pub fn build(self) -> Result<Network, String> {
+        if self.shapes.is_empty() {
+            return Err("A network needs at least one layer".to_string());
+        }
+
+        let layers: Vec<Layer> = self
+            .shapes
+            .iter()
+            .map(|&(input_dim, output_dim, activation)| {
+                let layer = match self.init {
+                    InitScheme::Random => Layer::random(input_dim, output_dim),
+                    InitScheme::Xavier => Layer::new_xavier(input_dim, output_dim),
+                    InitScheme::He => Layer::new_he(input_dim, output_dim),
+                };
+                layer.with_activation(activation)
+            })
+            .collect();
+
+        for (i, pair) in layers.windows(2).enumerate() {
+            if pair[0].output_dim() != pair[1].input_dim() {
+                return Err(format!(
+                    "Layer {} outputs {} values but layer {} expects {} inputs",
+                    i,
+                    pair[0].output_dim(),
+                    i + 1,
+                    pair[1].input_dim()
+                ));
+            }
+        }
+
+        Ok(Network::new(layers))
+    }
+}
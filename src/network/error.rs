@@ -0,0 +1,69 @@
+use crate::prelude::*;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::io;
+
+/// Errors produced while training, persisting or restoring a `Network`, or
+/// loading data.
+#[derive(Debug)]
+pub enum NetworkError {
+    /// The underlying file could not be created, written, or read.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The file contents are not a valid serialized network or data file.
+    Parse(String),
+    /// A training step overflowed the pre-activation, output or loss of
+    /// `layer`, or left a NaN or infinite weight, bias or spline control point
+    /// in it, usually because the learning rate is too high. `epoch` is known
+    /// when the step was taken by an epoch loop.
+    NonFinite { epoch: Option<usize>, layer: usize },
+    /// The network uses a feature that the requested export format cannot
+    /// represent.
+    Export(String),
+    /// The file was written in a format version this build cannot read.
+    VersionMismatch { found: u32, expected: u32 },
+}
+
+impl NetworkError {
+    /// Records the epoch in which a `NonFinite` error occurred.
+    #[cfg(feature = "std")]
+    pub(crate) fn in_epoch(self, epoch: usize) -> Self {
+        match self {
+            NetworkError::NonFinite { layer, .. } => NetworkError::NonFinite { epoch: Some(epoch), layer },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            NetworkError::Io(e) => write!(f, "I/O error: {}", e),
+            NetworkError::Parse(msg) => write!(f, "Parse error: {}", msg),
+            NetworkError::NonFinite { epoch: Some(epoch), layer } => write!(f, "Non-finite parameters in layer {} during epoch {}", layer, epoch),
+            NetworkError::NonFinite { epoch: None, layer } => write!(f, "Non-finite parameters in layer {}", layer),
+            NetworkError::Export(msg) => write!(f, "Export error: {}", msg),
+            NetworkError::VersionMismatch { found, expected } => write!(f, "Unsupported model file version {}, expected version {}", found, expected),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for NetworkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NetworkError::Io(e) => Some(e),
+            NetworkError::Parse(_) | NetworkError::NonFinite { .. } | NetworkError::Export(_) | NetworkError::VersionMismatch { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for NetworkError {
+    fn from(e: io::Error) -> Self {
+        NetworkError::Io(e)
+    }
+}
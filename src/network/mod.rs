@@ -1,3 +1,24 @@
+#[allow(clippy::module_inception)]
 pub mod network;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod optimizer;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "std")]
+mod onnx;
+pub mod quantize;
 
-pub use network::Network;
+pub use network::{clip_gradients, ensemble_predict, weighted_ensemble_predict, ForwardCache, Network};
+#[cfg(feature = "std")]
+pub use network::{Checkpoint, History, NetworkFile};
+pub use error::NetworkError;
+#[cfg(feature = "std")]
+pub use builder::{InitScheme, NetworkBuilder};
+#[cfg(feature = "std")]
+pub use optimizer::{Adam, Optimizer, Sgd};
+#[cfg(feature = "std")]
+pub use schedule::LrSchedule;
+pub use quantize::{QuantizedLayer, QuantizedNetwork};
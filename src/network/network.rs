@@ -1,83 +1,1099 @@
-use crate::data_structures::Layer;
-use crate::data_structures::Matrix;
-use crate::data_structures::Vector;
-use std::io::{self, Read, Write};
+use crate::prelude::*;
+use crate::data_structures::{BatchNorm, Float, Layer, Matrix, Vector};
+use crate::data_structures::{SplineGradients, SymbolicFn};
+use crate::utils::loss_functions::{huber, huber_gradient, CrossEntropyLoss, Loss, MseLoss};
+use crate::network::NetworkError;
+#[cfg(feature = "std")]
+use crate::network::{LrSchedule, NetworkBuilder, Optimizer};
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 use serde::{Deserialize, Serialize};
+use core::fmt;
+#[cfg(feature = "std")]
+use indicatif::ProgressBar;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "std")]
 use std::fs::File;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+/// A feed-forward stack of layers over the scalar type `T`, `f32` by default.
+/// Construction, the forward pass, `backward`, `update` and the single-sample
+/// `train` steps work for any `Float`, such as `f64`; the epoch loops,
+/// optimizers, evaluation and persistence are `f32` only.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Network {
-    pub layers: Vec<Layer>,
+pub struct Network<T = f32> {
+    pub layers: Vec<Layer<T>>,
+    /// The L2 penalty coefficient applied to the weights by `update`.
+    #[serde(default)]
+    pub weight_decay: f32,
+    /// The maximum L2 norm of each layer's gradients, if clipping is enabled.
+    #[serde(default)]
+    pub max_gradient_norm: Option<f32>,
+    /// The L1 penalty coefficient applied to the weights and spline control
+    /// points by `update`, pushing unimportant edges towards zero.
+    #[serde(default)]
+    pub l1_lambda: f32,
+    /// The coefficient of the edge entropy penalty applied by `update`, which
+    /// concentrates each layer on a few edges.
+    #[serde(default)]
+    pub lambda_entropy: f32,
+    /// Which layers are held fixed by `update`, indexed like `layers`. Missing
+    /// entries count as unfrozen.
+    #[serde(default)]
+    pub frozen: Vec<bool>,
+    /// `(src, dst)` pairs of layers set up by `tie_weights`, where the
+    /// weights of `dst` are the transpose of those of `src`.
+    #[serde(default)]
+    pub tied_weights: Vec<(usize, usize)>,
+    /// Whether the network is in training mode, enabling layer dropout.
+    #[serde(skip)]
+    pub training: bool,
+    /// Whether `train_epochs` draws a progress bar.
+    #[serde(skip)]
+    pub verbose: bool,
+    /// The gradients summed by `accumulate_gradients` since the last
+    /// `apply_accumulated`, with the number of samples they cover.
+    #[serde(skip)]
+    accumulated: Option<(Gradients<T>, usize)>,
+    /// The RNG that shuffles the samples of every epoch, if shuffling was
+    /// enabled by `with_shuffle_seed`.
+    #[serde(skip)]
+    shuffle_rng: Option<StdRng>,
 }
 
-impl Network {
-    pub fn new(layers: Vec<Layer>) -> Self {
-        Network { layers }
+/// The best point reached by `Network::train_with_checkpointing`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checkpoint {
+    /// The number of epochs trained when the best loss was reached, zero if
+    /// training never improved on the initial network.
+    pub epochs: usize,
+    /// The training loss at that point.
+    pub loss: f32,
+}
+
+/// The per-epoch training metrics recorded by `Network::fit`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    /// The `evaluate_batch` loss on the training set after each epoch.
+    pub loss: Vec<f32>,
+    /// The `evaluate_batch` accuracy on the training set after each epoch.
+    pub accuracy: Vec<f32>,
+}
+
+/// The contents of a file written by `Network::save_binary`: the network
+/// with the format version it was written in and some metadata.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkFile {
+    /// The version of the file format; `version` must stay the first field,
+    /// so readers can check it before decoding the rest.
+    pub version: u32,
+    /// When the file was written, in seconds since the Unix epoch.
+    pub created: String,
+    /// The `(input, output)` shape of every layer.
+    pub arch: Vec<(usize, usize)>,
+    pub network: Network,
+}
+
+#[cfg(feature = "std")]
+impl NetworkFile {
+    /// The format version this build reads and writes.
+    pub const VERSION: u32 = 1;
+
+    /// Wraps `network` with the current version and time.
+    pub fn new(network: Network) -> Self {
+        let created = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        NetworkFile { version: NetworkFile::VERSION, created: created.to_string(), arch: network.layer_shapes(), network }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), NetworkError> {
+        let bytes = bincode::serialize(self).map_err(|e| NetworkError::Parse(e.to_string()))?;
+        File::create(path)?.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Reads a file written by `save`, returning a `VersionMismatch` error
+    /// for any format version other than `VERSION`.
+    pub fn load(path: &str) -> Result<NetworkFile, NetworkError> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        let version: u32 = bincode::deserialize(&bytes).map_err(|e| NetworkError::Parse(e.to_string()))?;
+        if version != NetworkFile::VERSION {
+            return Err(NetworkError::VersionMismatch { found: version, expected: NetworkFile::VERSION });
+        }
+        bincode::deserialize(&bytes).map_err(|e| NetworkError::Parse(e.to_string()))
+    }
+}
+
+/// The gradients of every trainable parameter of every layer, in forward order.
+#[derive(Debug, Clone, PartialEq)]
+struct Gradients<T = f32> {
+    weights: Vec<Matrix<T>>,
+    biases: Vec<Vector<T>>,
+    splines: Vec<SplineGradients<T>>,
+    /// The gamma and beta gradients of each layer with batch normalization.
+    batch_norm: Vec<Option<(Vector<T>, Vector<T>)>>,
+    /// The gradient with respect to the network input, which is not a
+    /// parameter and so is neither accumulated nor scaled.
+    input: Vector<T>,
+}
+
+impl<T: Float> Gradients<T> {
+    fn accumulate(&mut self, other: &Gradients<T>) {
+        for (total, gradient) in self.weights.iter_mut().zip(&other.weights) {
+            total.add_mut(gradient).unwrap();
+        }
+        for (total, delta) in self.biases.iter_mut().zip(&other.biases) {
+            *total = total.add(delta).unwrap();
+        }
+        for (total, gradient) in self.splines.iter_mut().flatten().flatten().zip(other.splines.iter().flatten().flatten()) {
+            total.iter_mut().zip(gradient).for_each(|(t, &g)| *t += g);
+        }
+        for (total, gradient) in self.batch_norm.iter_mut().zip(&other.batch_norm) {
+            if let (Some((gamma, beta)), Some((gamma_grad, beta_grad))) = (total, gradient) {
+                *gamma = gamma.add(gamma_grad).unwrap();
+                *beta = beta.add(beta_grad).unwrap();
+            }
+        }
+    }
+
+    fn scale(&mut self, scale: T) {
+        self.weights.iter_mut().for_each(|gradient| gradient.scalar_multiply_mut(scale));
+        self.biases.iter_mut().for_each(|delta| *delta = delta.scalar_multiply(scale));
+        self.splines.iter_mut().flatten().flatten().flatten().for_each(|g| *g *= scale);
+        for (gamma, beta) in self.batch_norm.iter_mut().flatten() {
+            *gamma = gamma.scalar_multiply(scale);
+            *beta = beta.scalar_multiply(scale);
+        }
+    }
+}
+
+impl<T: Float> fmt::Display for Network<T> {
+    /// Formats a header with the layer and parameter counts followed by one
+    /// summary line per layer.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Network ({} layers, {} parameters)", self.layers.len(), self.num_parameters())?;
+        for (i, layer) in self.layers.iter().enumerate() {
+            write!(f, "\n  {}: {}", i, layer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rescales a layer's weight and bias gradients so that their combined L2 norm
+/// does not exceed `max_norm`. Gradients already within the bound are untouched.
+pub fn clip_gradients<T: Float>(weight_grad: &mut Matrix<T>, bias_grad: &mut Vector<T>, max_norm: T) {
+    let norm = (weight_grad.map(|g| g * g).sum() + bias_grad.dot(bias_grad).unwrap()).sqrt();
+    if norm > max_norm {
+        let scale = max_norm / norm;
+        *weight_grad = weight_grad.scalar_multiply(scale);
+        *bias_grad = bias_grad.scalar_multiply(scale);
+    }
+}
+
+/// Averages the outputs of several networks on the same input. Fails if
+/// there are no networks or their output dimensions disagree.
+pub fn ensemble_predict(networks: &[Network], input: Vector) -> Result<Vector, String> {
+    weighted_ensemble_predict(networks, &vec![1.0; networks.len()], input)
+}
+
+/// Like `ensemble_predict`, but weights each network's output, dividing by the
+/// total weight. Fails unless there is one weight per network and the weights
+/// have a nonzero sum.
+pub fn weighted_ensemble_predict(networks: &[Network], weights: &[f32], input: Vector) -> Result<Vector, String> {
+    if networks.is_empty() {
+        return Err("An ensemble needs at least one network".to_string());
+    }
+    if weights.len() != networks.len() {
+        return Err(format!("Expected one weight per network, got {} for {} networks", weights.len(), networks.len()));
+    }
+    let total: f32 = weights.iter().sum();
+    if total == 0.0 {
+        return Err("Ensemble weights must not sum to zero".to_string());
+    }
+
+    let mut sum = networks[0].forward(input.clone()).scalar_multiply(weights[0]);
+    for (i, (network, &weight)) in networks.iter().zip(weights).enumerate().skip(1) {
+        let output = network.forward(input.clone());
+        if output.len() != sum.len() {
+            return Err(format!("Network {} has {} outputs, but network 0 has {}", i, output.len(), sum.len()));
+        }
+        sum = sum.add(&output.scalar_multiply(weight))?;
+    }
+    Ok(sum.scalar_multiply(1.0 / total))
+}
+
+/// The intermediate values of a forward pass, as produced by
+/// `Network::forward_cached`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardCache<T = f32> {
+    /// The network input followed by the output of every layer.
+    pub activations: Vec<Vector<T>>,
+    /// The input of every layer's activation: its output `z`, after batch
+    /// normalization if the layer has it.
+    pub pre_activations: Vec<Vector<T>>,
+    /// The standardized output `x̂` of every layer with batch normalization.
+    pub normalized: Vec<Option<Vector<T>>>,
+    /// The dropout mask sampled for every layer, if dropout was active.
+    pub dropout_masks: Vec<Option<Vector<T>>>,
+}
+
+impl<T: Float> Network<T> {
+    pub fn new(layers: Vec<Layer<T>>) -> Self {
+        Network { layers, weight_decay: 0.0, max_gradient_norm: None, l1_lambda: 0.0, lambda_entropy: 0.0, frozen: Vec::new(), tied_weights: Vec::new(), training: false, verbose: false, accumulated: None, shuffle_rng: None }
+    }
+
+    /// Sets the L2 weight decay coefficient used by `update`.
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> Self {
+        self.weight_decay = weight_decay;
+        self
+    }
+
+    /// Sets the L1 penalty coefficient used by `update`.
+    pub fn with_l1(mut self, l1_lambda: f32) -> Self {
+        self.l1_lambda = l1_lambda;
+        self
+    }
+
+    /// Sets the edge entropy penalty coefficient used by `update`.
+    pub fn with_entropy_regularization(mut self, lambda_entropy: f32) -> Self {
+        self.lambda_entropy = lambda_entropy;
+        self
+    }
+
+    /// Enables gradient clipping so that each layer's gradients have an L2 norm
+    /// of at most `max_norm` before they are applied.
+    pub fn with_gradient_clipping(mut self, max_norm: f32) -> Self {
+        self.max_gradient_norm = Some(max_norm);
+        self
+    }
+
+    /// Makes `train_epoch`, and the epoch loops built on it, visit the samples
+    /// in a new random order every epoch, drawn from an RNG seeded by `seed`
+    /// so that runs are reproducible. Without it samples are visited in order.
+    pub fn with_shuffle_seed(mut self, seed: u64) -> Self {
+        self.shuffle_rng = Some(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    /// Holds the layer at `idx` fixed during `update` and optimizer steps.
+    pub fn freeze_layer(&mut self, idx: usize) {
+        self.set_frozen(idx, true);
+    }
+
+    /// Lets the layer at `idx` train again after `freeze_layer`.
+    pub fn unfreeze_layer(&mut self, idx: usize) {
+        self.set_frozen(idx, false);
+    }
+
+    /// Returns whether the layer at `idx` is frozen.
+    pub fn is_frozen(&self, idx: usize) -> bool {
+        self.frozen.get(idx).copied().unwrap_or(false)
+    }
+
+    fn set_frozen(&mut self, idx: usize, frozen: bool) {
+        assert!(idx < self.layers.len(), "Layer index {} out of range for {} layers", idx, self.layers.len());
+        self.frozen.resize(self.layers.len(), false);
+        self.frozen[idx] = frozen;
+    }
+
+    /// Ties the weights of layer `dst` to the transpose of those of layer
+    /// `src`, as in a tied autoencoder. `dst` takes the transposed weights
+    /// now, and from then on every update trains the shared weights with the
+    /// gradients of both layers. Fails unless the layer shapes are transposes
+    /// of each other.
+    pub fn tie_weights(&mut self, src: usize, dst: usize) -> Result<(), String> {
+        let count = self.layers.len();
+        if src >= count || dst >= count || src == dst {
+            return Err(format!("Cannot tie layer {} to layer {} in a network of {} layers", dst, src, count));
+        }
+        let (src_shape, dst_shape) = (self.layers[src].weights.shape(), self.layers[dst].weights.shape());
+        if dst_shape != (src_shape.1, src_shape.0) {
+            return Err(format!(
+                "Tied weights need transposed shapes, got {}x{} and {}x{}",
+                src_shape.0, src_shape.1, dst_shape.0, dst_shape.1
+            ));
+        }
+        self.tied_weights.push((src, dst));
+        self.sync_tied_weights();
+        Ok(())
+    }
+
+    /// Copies the transposed weights of every tied source layer to its
+    /// destination.
+    fn sync_tied_weights(&mut self) {
+        for &(src, dst) in &self.tied_weights {
+            self.layers[dst].weights = self.layers[src].weights.transpose();
+        }
+    }
+
+    /// Resets the gradient buffers of every layer to zero.
+    pub fn zero_grad(&mut self) {
+        self.layers.iter_mut().for_each(Layer::zero_grad);
+    }
+
+    /// Switches between training mode, where layer dropout is applied, and
+    /// evaluation mode, where the forward pass is deterministic.
+    pub fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+
+    /// Enables or disables the progress bar of `train_epochs`. Networks are
+    /// silent by default.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /// Returns the dropout mask for a layer output, or `None` when dropout is
+    /// inactive for it.
+    #[cfg(feature = "std")]
+    fn dropout_mask(&self, layer: &Layer<T>) -> Option<Vector<T>> {
+        if self.training && layer.dropout > 0.0 {
+            Some(layer.dropout_mask())
+        } else {
+            None
+        }
     }
 
-    pub fn forward(&self, input: Vector) -> Vector {
+    /// Without `std` there is no RNG to sample dropout masks from, so the
+    /// forward pass is deterministic even in training mode.
+    #[cfg(not(feature = "std"))]
+    fn dropout_mask(&self, _layer: &Layer<T>) -> Option<Vector<T>> {
+        None
+    }
+
+    /// Runs the forward pass for a single sample. Panics with the expected
+    /// and received lengths if the input does not fit the first layer.
+    pub fn forward(&self, input: Vector<T>) -> Vector<T> {
+        self.check_input_dim(input.len());
         let mut output = input.clone();
         for layer in &self.layers {
             output = layer.forward(&output);
+            if let Some(mask) = self.dropout_mask(layer) {
+                output = output.elementwise_multiply(&mask);
+            }
         }
         output
     }
 
-    pub fn backward(&self, input: Vector, target: Vector) -> (Matrix, Vector) {
-        let mut output = input.clone();
-        let mut outputs = vec![output.clone()];
-        let mut inputs = vec![input.clone()];
+    fn check_input_dim(&self, len: usize) {
+        if let Some(first) = self.layers.first() {
+            assert!(len == first.input_dim(), "Expected an input of dimension {} for the first layer, got {}", first.input_dim(), len);
+        }
+    }
+
+    /// Performs the forward propagation for a batch of inputs, one sample per
+    /// row, returning one output row per sample. In training mode batch
+    /// normalization uses the statistics of the batch.
+    pub fn forward_batch(&self, inputs: &Matrix<T>) -> Matrix<T> {
+        if !inputs.is_empty() {
+            self.check_input_dim(inputs.col_count());
+        }
+        let mut outputs = inputs.clone();
+        for layer in &self.layers {
+            outputs = if self.training { layer.forward_batch_training(&outputs) } else { layer.forward_batch(&outputs) };
+        }
+        outputs
+    }
+
+    /// Like `forward`, but without dropout.
+    pub fn predict(&self, input: Vector<T>) -> Vector<T> {
+        self.check_input_dim(input.len());
+        let mut output = input;
         for layer in &self.layers {
             output = layer.forward(&output);
-            outputs.push(output.clone());
-            inputs.push(output.clone());
         }
-        let mut error = outputs.last().unwrap().subtract(&target);
-        let mut weight_gradients = self.layers.last().unwrap().weight_gradients(&inputs[inputs.len() - 2], &outputs[outputs.len() - 2], &error);
-        let mut delta = self.layers.last().unwrap().delta(&error, &outputs[outputs.len() - 2]);
-        for i in (1..self.layers.len()).rev() {
-            error = self.layers[i].weights.transpose().multiply_with_vector(&error).unwrap();
-            let gradient = outputs[i].elementwise_multiply(&outputs[i].subtract(&Vector::ones(outputs[i].len())));
-            delta = self.layers[i].delta(&error, &gradient);
-            weight_gradients = weight_gradients.add(&self.layers[i].weight_gradients(&inputs[i - 1], &outputs[i - 1], &delta)).unwrap();
+        output
+    }
+
+    pub fn predict_batch(&self, inputs: &[Vector<T>]) -> Vec<Vector<T>> {
+        #[cfg(feature = "parallel")]
+        return inputs.par_iter().map(|input| self.predict(input.clone())).collect();
+        #[cfg(not(feature = "parallel"))]
+        inputs.iter().map(|input| self.predict(input.clone())).collect()
+    }
+
+    /// Returns the softmax of the network output: the predicted probability
+    /// of each class, summing to one.
+    pub fn predict_proba(&self, input: Vector<T>) -> Vector<T> {
+        self.forward(input).softmax()
+    }
+
+    /// Returns whether both networks have the same layers up to a tolerance:
+    /// the same architecture, with every parameter differing by at most
+    /// `tol`. Unlike `==`, this survives the round-off of a text round trip.
+    pub fn equivalent(&self, other: &Network<T>, tol: T) -> bool {
+        self.layers.len() == other.layers.len() && self.layers.iter().zip(&other.layers).all(|(a, b)| a.equivalent(b, tol))
+    }
+
+    /// Returns the total number of trainable parameters across all layers.
+    pub fn num_parameters(&self) -> usize {
+        self.layers.iter().map(Layer::num_parameters).sum()
+    }
+
+    /// Returns the `(input_dim, output_dim)` of each layer.
+    pub fn layer_shapes(&self) -> Vec<(usize, usize)> {
+        self.layers.iter().map(|layer| (layer.input_dim(), layer.output_dim())).collect()
+    }
+
+    /// Returns a table of the layers with their type, input and output shape
+    /// and parameter count, followed by the total number of parameters.
+    pub fn summary(&self) -> String {
+        let rule = "-".repeat(48);
+        let mut summary = format!("{:<6}{:<18}{:>8}{:>8}{:>8}\n{}\n", "Layer", "Type", "Input", "Output", "Params", rule);
+        for (i, (layer, (input_dim, output_dim))) in self.layers.iter().zip(self.layer_shapes()).enumerate() {
+            let kind = if layer.splines.is_empty() { "Dense" } else { "KAN" };
+            let kind = format!("{} ({:?})", kind, layer.activation);
+            summary += &format!("{:<6}{:<18}{:>8}{:>8}{:>8}\n", i, kind, input_dim, output_dim, layer.num_parameters());
         }
-        (weight_gradients, delta)
+        summary += &format!("{}\nTotal parameters: {}", rule, self.num_parameters());
+        summary
     }
 
-    pub fn update(&mut self, weight_gradients: &Matrix, delta: &Vector, learning_rate: f32) {
-        for layer in self.layers.iter_mut() {
-            layer.update(weight_gradients, delta, learning_rate);
+    /// Returns a Graphviz DOT graph of the network, with one node per neuron,
+    /// from the inputs `x` through the hidden units `h` to the outputs `y`,
+    /// and one edge per connection. Render it with `dot -Tsvg`.
+    pub fn to_dot(&self) -> String {
+        self.dot(false)
+    }
+
+    /// Like `to_dot`, but labels every edge with the magnitude of its weight.
+    pub fn to_dot_with_weights(&self) -> String {
+        self.dot(true)
+    }
+
+    fn dot(&self, weight_labels: bool) -> String {
+        let depth = self.layers.len();
+        let node = |level: usize, i: usize| match level {
+            0 => format!("x{}", i),
+            _ if level == depth => format!("y{}", i),
+            _ => format!("h{}_{}", level, i),
+        };
+
+        let mut dot = "digraph network {\n    rankdir=LR;\n".to_string();
+        let widths = self.layers.first().map(|layer| layer.input_dim()).into_iter().chain(self.layers.iter().map(|layer| layer.output_dim()));
+        for (level, width) in widths.enumerate() {
+            let nodes: Vec<String> = (0..width).map(|i| node(level, i)).collect();
+            dot += &format!("    {{ rank=same; {}; }}\n", nodes.join("; "));
         }
+        for (l, layer) in self.layers.iter().enumerate() {
+            for (i, row) in layer.weights.rows.iter().enumerate() {
+                for (j, weight) in row.elements.iter().enumerate() {
+                    let label = if weight_labels { format!(" [label=\"{:.2}\"]", weight.abs()) } else { String::new() };
+                    dot += &format!("    {} -> {}{};\n", node(l, i), node(l + 1, j), label);
+                }
+            }
+        }
+        dot + "}\n"
     }
 
-    pub fn train(&mut self, input: Vector, target: Vector, learning_rate: f32) {
-        self.forward(input.clone());
-        let (weight_gradients, delta) = self.backward(input, target);
-        self.update(&weight_gradients, &delta, learning_rate);
+    pub fn biases(&self) -> Vec<Vector<T>> {
+        self.layers.iter().map(|layer| layer.biases.clone()).collect()
     }
 
+    pub fn weights(&self) -> Vec<Matrix<T>> {
+        self.layers.iter().map(|layer| layer.weights.clone()).collect()
+    }
 
-    pub fn predict(&self, input: Vector) -> Vector {
-      
-        let mut output = input;
+    /// Runs the forward pass for a single sample, recording what `backward`
+    /// needs: every layer's input and output, its pre-activation `z`, and the
+    /// dropout mask it sampled. Each call builds a fresh cache.
+    ///
+    /// Batch normalization uses the running estimates, since a single sample
+    /// has no batch statistics.
+    pub fn forward_cached(&self, input: Vector<T>) -> ForwardCache<T> {
+        self.check_input_dim(input.len());
+        let mut activations = vec![input];
+        let mut pre_activations = Vec::with_capacity(self.layers.len());
+        let mut normalized = Vec::with_capacity(self.layers.len());
+        let mut dropout_masks = Vec::with_capacity(self.layers.len());
         for layer in &self.layers {
-            output = layer.forward(&output);
+            let mut pre_activation = layer.pre_activation(activations.last().unwrap());
+            if let Some(batch_norm) = &layer.batch_norm {
+                let standardized = batch_norm.normalized(&pre_activation);
+                pre_activation = batch_norm.forward(&pre_activation);
+                normalized.push(Some(standardized));
+            } else {
+                normalized.push(None);
+            }
+            let mut output = layer.add_residual(activations.last().unwrap(), layer.activation.apply(&pre_activation));
+            let mask = self.dropout_mask(layer);
+            if let Some(mask) = &mask {
+                output = output.elementwise_multiply(mask);
+            }
+            activations.push(output);
+            pre_activations.push(pre_activation);
+            dropout_masks.push(mask);
+        }
+        ForwardCache { activations, pre_activations, normalized, dropout_masks }
+    }
+
+    /// Backpropagates the error for a single sample and returns the weight
+    /// gradients and deltas of every layer, in forward order.
+    pub fn backward(&self, input: Vector<T>, target: Vector<T>) -> (Vec<Matrix<T>>, Vec<Vector<T>>) {
+        self.backward_with_output_gradient(input, |output| output.subtract(&target))
+    }
+
+    /// Like `backward`, but differentiates the summed Huber loss with threshold
+    /// `delta`, which limits the pull of outliers.
+    pub fn backward_huber(&self, input: Vector<T>, target: Vector<T>, delta: T) -> (Vec<Matrix<T>>, Vec<Vector<T>>) {
+        self.backward_with_output_gradient(input, |output| huber_gradient(output, &target, delta))
+    }
+
+    /// Like `backward`, but differentiates `loss` instead of the squared error.
+    pub fn backward_with_loss(&self, input: Vector<T>, target: Vector<T>, loss: &dyn Loss<T>) -> (Vec<Matrix<T>>, Vec<Vector<T>>) {
+        self.backward_with_output_gradient(input, |output| loss.gradient(output, &target))
+    }
+
+    /// Backpropagates the gradient of a loss with respect to the network
+    /// output, computed from the output by `output_gradient`.
+    pub fn backward_with_output_gradient(&self, input: Vector<T>, output_gradient: impl Fn(&Vector<T>) -> Vector<T>) -> (Vec<Matrix<T>>, Vec<Vector<T>>) {
+        let gradients = self.backward_from_cache(&self.forward_cached(input), output_gradient);
+        (gradients.weights, gradients.biases)
+    }
+
+    /// Like `backward`, but also returns the gradients of the edge spline
+    /// control points of every layer.
+    #[allow(clippy::type_complexity)]
+    pub fn backward_with_splines(&self, input: Vector<T>, target: Vector<T>) -> (Vec<Matrix<T>>, Vec<Vector<T>>, Vec<SplineGradients<T>>) {
+        let gradients = self.gradients(input, target);
+        (gradients.weights, gradients.biases, gradients.splines)
+    }
+
+    /// Returns the weight gradient and delta of the layer at `layer_idx` for a
+    /// single sample, as `backward` would for that layer.
+    pub fn grad_wrt_layer(&self, layer_idx: usize, input: &Vector<T>, target: &Vector<T>) -> (Matrix<T>, Vector<T>) {
+        assert!(layer_idx < self.layers.len(), "Layer index {} out of range for {} layers", layer_idx, self.layers.len());
+        let mut gradients = self.gradients(input.clone(), target.clone());
+        (gradients.weights.swap_remove(layer_idx), gradients.biases.swap_remove(layer_idx))
+    }
+
+    fn gradients(&self, input: Vector<T>, target: Vector<T>) -> Gradients<T> {
+        self.backward_from_cache(&self.forward_cached(input), |output| output.subtract(&target))
+    }
+
+    /// Like `gradients`, but for `loss`, and failing with `NonFinite` naming
+    /// the first layer whose pre-activation or output overflows, or the last
+    /// layer if the loss itself does, instead of backpropagating NaNs.
+    fn gradients_with_loss(&self, input: Vector<T>, target: Vector<T>, loss: &dyn Loss<T>) -> Result<Gradients<T>, NetworkError> {
+        let cache = self.forward_cached(input);
+        let overflowing_layer = (0..self.layers.len()).find(|&i| !cache.pre_activations[i].is_finite() || !cache.activations[i + 1].is_finite());
+        if let Some(layer) = overflowing_layer {
+            return Err(NetworkError::NonFinite { epoch: None, layer });
+        }
+        if !loss.value(cache.activations.last().unwrap(), &target).is_finite() {
+            return Err(NetworkError::NonFinite { epoch: None, layer: self.layers.len().saturating_sub(1) });
+        }
+        Ok(self.backward_from_cache(&cache, |output| loss.gradient(output, &target)))
+    }
+
+    fn backward_from_cache(&self, cache: &ForwardCache<T>, output_gradient: impl Fn(&Vector<T>) -> Vector<T>) -> Gradients<T> {
+        let ForwardCache { activations, pre_activations, normalized, dropout_masks } = cache;
+
+        let mut delta = output_gradient(activations.last().unwrap());
+        let mut weight_gradients = Vec::with_capacity(self.layers.len());
+        let mut deltas = Vec::with_capacity(self.layers.len());
+        let mut spline_gradients = Vec::with_capacity(self.layers.len());
+        let mut batch_norm_gradients = Vec::with_capacity(self.layers.len());
+        for (i, layer) in self.layers.iter().enumerate().rev() {
+            if let Some(mask) = &dropout_masks[i] {
+                delta = delta.elementwise_multiply(mask);
+            }
+            let (input_delta, layer_delta, activation_delta) = layer.backward_from(&activations[i], &pre_activations[i], &delta);
+            batch_norm_gradients.push(normalized[i].as_ref().map(|standardized| (activation_delta.elementwise_multiply(standardized), activation_delta)));
+            weight_gradients.push(layer.weight_gradients(&activations[i], &layer_delta));
+            spline_gradients.push(layer.spline_gradients(&activations[i], &layer_delta));
+            deltas.push(layer_delta);
+            delta = input_delta;
+        }
+        weight_gradients.reverse();
+        deltas.reverse();
+        spline_gradients.reverse();
+        batch_norm_gradients.reverse();
+        Gradients { weights: weight_gradients, biases: deltas, splines: spline_gradients, batch_norm: batch_norm_gradients, input: delta }
+    }
+
+    /// Returns the gradient of output `target_output` with respect to each
+    /// input dimension, a saliency map of which features drive that output.
+    /// Panics if `target_output` is not an output index.
+    pub fn input_saliency(&self, input: &Vector<T>, target_output: usize) -> Vector<T> {
+        let cache = self.forward_cached(input.clone());
+        let outputs = cache.activations.last().unwrap().len();
+        assert!(target_output < outputs, "Output {} out of range for a network with {} outputs", target_output, outputs);
+        let gradients = self.backward_from_cache(&cache, |output| {
+            let mut seed = Vector::zeros(output.len());
+            seed.elements[target_output] = T::one();
+            seed
+        });
+        gradients.input
+    }
+
+    /// Returns the L2 norm of all weight and bias gradients from `backward`
+    /// taken together. Logged per epoch, it exposes vanishing or exploding
+    /// gradients.
+    pub fn gradient_norm(&self, input: &Vector<T>, target: &Vector<T>) -> T {
+        let (weight_gradients, deltas) = self.backward(input.clone(), target.clone());
+        let weight_sum: T = weight_gradients.iter().map(|g| g.map(|x| x * x).sum()).sum();
+        let bias_sum: T = deltas.iter().map(|d| d.dot(d).unwrap()).sum();
+        (weight_sum + bias_sum).sqrt()
+    }
+
+    /// Applies one gradient descent step to every layer using the per-layer
+    /// gradients returned by `backward`. With a nonzero `weight_decay`, each
+    /// layer's weights are first shrunk by `learning_rate * weight_decay * w`;
+    /// with a nonzero `l1_lambda`, the weights and spline control points are
+    /// moved towards zero by `learning_rate * l1_lambda * sign(w)`; with a
+    /// nonzero `lambda_entropy`, they take a step of `learning_rate *
+    /// lambda_entropy` down the edge entropy. Frozen layers are left unchanged.
+    pub fn update(&mut self, weight_gradients: &[Matrix<T>], deltas: &[Vector<T>], learning_rate: T) {
+        let learning_rates = vec![learning_rate; self.layers.len()];
+        self.update_layerwise(weight_gradients, deltas, &learning_rates);
+    }
+
+    /// Like `update`, but with a separate learning rate for every layer, for
+    /// example a smaller one for pretrained early layers. Panics unless there
+    /// is one rate per layer.
+    pub fn update_layerwise(&mut self, weight_gradients: &[Matrix<T>], deltas: &[Vector<T>], learning_rates: &[T]) {
+        self.check_learning_rates(learning_rates);
+        let tied_gradients;
+        let weight_gradients = if self.tied_weights.is_empty() {
+            weight_gradients
+        } else {
+            tied_gradients = self.tie_gradients(weight_gradients.to_vec());
+            &tied_gradients
+        };
+        // The penalty coefficients are stored as `f32` whatever the scalar type.
+        let [weight_decay, l1_lambda, lambda_entropy] = [self.weight_decay, self.l1_lambda, self.lambda_entropy].map(|x| T::constant(f64::from(x)));
+        for (index, (((layer, weight_gradient), delta), &learning_rate)) in self.layers.iter_mut().zip(weight_gradients).zip(deltas).zip(learning_rates).enumerate() {
+            if self.frozen.get(index).copied().unwrap_or(false) {
+                continue;
+            }
+            let (mut weight_gradient, mut delta) = (weight_gradient.clone(), delta.clone());
+            if let Some(max_norm) = self.max_gradient_norm {
+                clip_gradients(&mut weight_gradient, &mut delta, T::constant(f64::from(max_norm)));
+            }
+            if self.weight_decay != 0.0 {
+                layer.weights.scalar_multiply_mut(T::one() - learning_rate * weight_decay);
+            }
+            if self.l1_lambda != 0.0 {
+                layer.l1_step(learning_rate * l1_lambda);
+            }
+            if self.lambda_entropy != 0.0 {
+                layer.entropy_step(learning_rate * lambda_entropy);
+            }
+            layer.update(&weight_gradient, &delta, learning_rate);
+        }
+        self.sync_tied_weights();
+    }
+
+    /// Adds the transposed weight gradient of every tied destination layer to
+    /// that of its source, so the shared weights follow both.
+    fn tie_gradients(&self, mut weight_gradients: Vec<Matrix<T>>) -> Vec<Matrix<T>> {
+        for &(src, dst) in &self.tied_weights {
+            let mirrored = weight_gradients[dst].transpose();
+            weight_gradients[src].add_mut(&mirrored).unwrap();
+        }
+        weight_gradients
+    }
+
+    fn check_learning_rates(&self, learning_rates: &[T]) {
+        assert_eq!(
+            learning_rates.len(),
+            self.layers.len(),
+            "Expected one learning rate per layer, got {} for {} layers",
+            learning_rates.len(),
+            self.layers.len()
+        );
+    }
+
+    /// Runs `backward` and adds the resulting gradients to every layer's
+    /// `weight_grad` and `bias_grad` buffers, which are zeroed first if they
+    /// do not match the layer's shape.
+    pub fn backward_into_grads(&mut self, input: Vector<T>, target: Vector<T>) {
+        let (weight_gradients, deltas) = self.backward(input, target);
+        for ((layer, weight_gradient), delta) in self.layers.iter_mut().zip(&weight_gradients).zip(&deltas) {
+            if layer.weight_grad.shape() != weight_gradient.shape() || layer.bias_grad.len() != delta.len() {
+                layer.zero_grad();
+            }
+            layer.weight_grad.add_mut(weight_gradient).unwrap();
+            layer.bias_grad = layer.bias_grad.add(delta).unwrap();
+        }
+    }
+
+    /// Takes one `update` step with the gradients stored in the layer buffers.
+    /// The buffers are left as they are; call `zero_grad` before the next
+    /// `backward_into_grads`.
+    pub fn step(&mut self, learning_rate: T) {
+        let weight_gradients: Vec<Matrix<T>> = self.layers.iter().map(|layer| layer.weight_grad.clone()).collect();
+        let deltas: Vec<Vector<T>> = self.layers.iter().map(|layer| layer.bias_grad.clone()).collect();
+        self.update(&weight_gradients, &deltas, learning_rate);
+    }
+
+    /// Takes one gradient descent step on a single sample, training the edge
+    /// splines and batch normalization as well as the weights and biases. Use `train_epoch` to
+    /// train over a set of samples.
+    ///
+    /// Returns a `NonFinite` error, leaving the parameters unchanged, if the
+    /// forward pass or loss overflows, and also if the step leaves any
+    /// parameter NaN or infinite.
+    pub fn train(&mut self, input: Vector<T>, target: Vector<T>, learning_rate: T) -> Result<(), NetworkError> {
+        self.train_with_loss(input, target, learning_rate, &MseLoss)
+    }
+
+    /// Like `train`, but with a separate learning rate for every layer; a
+    /// layer with rate zero is left unchanged. Panics unless there is one rate
+    /// per layer.
+    pub fn train_layerwise(&mut self, input: Vector<T>, target: Vector<T>, learning_rates: &[T]) -> Result<(), NetworkError> {
+        self.check_learning_rates(learning_rates);
+        self.train_step(input, target, learning_rates, &MseLoss)
+    }
+
+    /// Like `train`, but descends the gradient of `loss` instead of the
+    /// squared error; `train` is this with `MseLoss`.
+    pub fn train_with_loss(&mut self, input: Vector<T>, target: Vector<T>, learning_rate: T, loss: &dyn Loss<T>) -> Result<(), NetworkError> {
+        let learning_rates = vec![learning_rate; self.layers.len()];
+        self.train_step(input, target, &learning_rates, loss)
+    }
+
+    fn train_step(&mut self, input: Vector<T>, target: Vector<T>, learning_rates: &[T], loss: &dyn Loss<T>) -> Result<(), NetworkError> {
+        let gradients = self.gradients_with_loss(input, target, loss)?;
+        self.apply_gradients(&gradients, learning_rates);
+        match self.first_non_finite_layer() {
+            Some(layer) => Err(NetworkError::NonFinite { epoch: None, layer }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether any weight, bias or spline control point is NaN or
+    /// infinite.
+    pub fn has_nan(&self) -> bool {
+        self.first_non_finite_layer().is_some()
+    }
+
+    fn first_non_finite_layer(&self) -> Option<usize> {
+        self.layers.iter().position(|layer| {
+            layer.weights.rows.iter().flat_map(|row| &row.elements).any(|w| !w.is_finite())
+                || layer.biases.elements.iter().any(|b| !b.is_finite())
+                || layer.splines.iter().flatten().flat_map(|spline| &spline.control_points).any(|c| !c.is_finite())
+        })
+    }
+
+    fn apply_gradients(&mut self, gradients: &Gradients<T>, learning_rates: &[T]) {
+        self.update_layerwise(&gradients.weights, &gradients.biases, learning_rates);
+        for (index, (((layer, spline_gradients), batch_norm_gradients), &learning_rate)) in
+            self.layers.iter_mut().zip(&gradients.splines).zip(&gradients.batch_norm).zip(learning_rates).enumerate()
+        {
+            if self.frozen.get(index).copied().unwrap_or(false) {
+                continue;
+            }
+            layer.update_splines(spline_gradients, learning_rate);
+            if let (Some(batch_norm), Some((gamma_grad, beta_grad))) = (&mut layer.batch_norm, batch_norm_gradients) {
+                batch_norm.update(gamma_grad, beta_grad, learning_rate);
+            }
+        }
+    }
+
+    /// Takes a gradient descent step on the edge spline control points of
+    /// every layer that is not frozen.
+    pub fn update_splines(&mut self, spline_gradients: &[SplineGradients<T>], learning_rate: T) {
+        for (index, (layer, gradients)) in self.layers.iter_mut().zip(spline_gradients).enumerate() {
+            if !self.frozen.get(index).copied().unwrap_or(false) {
+                layer.update_splines(gradients, learning_rate);
+            }
+        }
+    }
+
+}
+
+impl Network {
+    /// Starts building a network layer by layer.
+    #[cfg(feature = "std")]
+    pub fn builder() -> NetworkBuilder {
+        NetworkBuilder::new()
+    }
+
+    /// Creates a network of random layers with the given `(input_dim, output_dim)`
+    /// shapes, drawing every layer from one RNG seeded by `seed` so that the
+    /// same seed always gives the same network.
+    pub fn new_seeded(shapes: &[(usize, usize)], seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Network::new(shapes.iter().map(|&(input_dim, output_dim)| Layer::random_with_rng(input_dim, output_dim, &mut rng)).collect())
+    }
+
+    /// Creates a network with the same layer shapes, activations and settings
+    /// as this one but freshly initialized parameters drawn from an RNG
+    /// seeded by `seed`: random weights, biases and spline control points,
+    /// and batch normalization reset to the identity. Bias-free layers keep
+    /// zero biases.
+    pub fn clone_architecture(&self, seed: u64) -> Network {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut network = self.clone();
+        for layer in &mut network.layers {
+            let (input_dim, output_dim) = (layer.input_dim(), layer.output_dim());
+            layer.weights = Matrix::random_with_rng(input_dim, output_dim, &mut rng);
+            layer.biases = if layer.use_bias { Vector::random_with_rng(output_dim, &mut rng) } else { Vector::zeros(output_dim) };
+            for spline in layer.splines.iter_mut().flatten() {
+                spline.control_points.iter_mut().for_each(|c| *c = rng.gen_range(-0.1..0.1));
+            }
+            if let Some(batch_norm) = &mut layer.batch_norm {
+                *batch_norm = BatchNorm::new(output_dim);
+            }
+        }
+        network.sync_tied_weights();
+        network.zero_grad();
+        network.accumulated = None;
+        network
+    }
+
+    /// Counts the weights of layer `layer` in `bins` equal-width bins spanning
+    /// their range, returning `(bin_center, count)` pairs.
+    ///
+    /// A layer without weights gives no bins, and one whose weights are all
+    /// equal gives a single bin at that value.
+    pub fn weight_histogram(&self, layer: usize, bins: usize) -> Vec<(f32, usize)> {
+        let values: Vec<f32> = self.layers[layer].weights.rows.iter().flat_map(|row| row.elements.iter().copied()).collect();
+        if values.is_empty() || bins == 0 {
+            return Vec::new();
+        }
+
+        let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        if min == max {
+            return vec![(min, values.len())];
+        }
+
+        let width = (max - min) / bins as f32;
+        let mut counts = vec![0; bins];
+        for value in values {
+            counts[(((value - min) / width) as usize).min(bins - 1)] += 1;
+        }
+        counts.into_iter().enumerate().map(|(i, count)| (min + (i as f32 + 0.5) * width, count)).collect()
+    }
+
+    /// Returns the edge entropy of every layer summed, the regularization
+    /// term that `lambda_entropy` weights in the training objective.
+    pub fn entropy_regularization(&self) -> f32 {
+        self.layers.iter().map(Layer::edge_entropy).sum()
+    }
+
+    /// Returns the input followed by the output of every layer, in order, for
+    /// visualizing or debugging what each layer computes.
+    pub fn forward_with_activations(&self, input: Vector) -> Vec<Vector> {
+        self.forward_cached(input).activations
+    }
+
+    /// Folds the statistics of a batch of inputs, one sample per row, into the
+    /// running estimates of every batch normalization layer.
+    pub fn update_batch_norm_statistics(&mut self, inputs: &Matrix) {
+        let mut outputs = inputs.clone();
+        for layer in &mut self.layers {
+            let pre_activations = layer.pre_activation_batch(&outputs);
+            if let Some(batch_norm) = &mut layer.batch_norm {
+                batch_norm.update_running_statistics(&pre_activations);
+            }
+            outputs = layer.forward_batch_training(&outputs);
+        }
+    }
+
+    /// Learns online from a single sample: takes one `train` step and returns
+    /// the sample's `mse_loss` before the update.
+    pub fn partial_fit(&mut self, input: Vector, target: Vector, learning_rate: f32) -> Result<f32, NetworkError> {
+        let loss = self.mse_loss(input.clone(), target.clone());
+        self.train(input, target, learning_rate)?;
+        Ok(loss)
+    }
+
+    /// Compares the gradients from `backward` with central finite differences
+    /// `(L(w + epsilon) - L(w - epsilon)) / (2 epsilon)` of the loss it
+    /// differentiates, `L = 0.5 |output - target|²`, for every weight and bias.
+    ///
+    /// Returns the largest relative error `|a - n| / max(|a| + |n|, 1)`, which
+    /// falls back to the absolute error for gradients near zero. Dropout is
+    /// disabled for the check. Meant as a debugging aid for backpropagation.
+    pub fn gradient_check(&self, input: &Vector, target: &Vector, epsilon: f32) -> f32 {
+        let mut network = self.clone();
+        network.training = false;
+        let (weight_gradients, deltas) = network.backward(input.clone(), target.clone());
+        let loss = |network: &Network| {
+            let error = network.predict(input.clone()).subtract(target);
+            0.5 * error.dot(&error).unwrap()
+        };
+        let relative_error = |analytic: f32, numeric: f32| (analytic - numeric).abs() / (analytic.abs() + numeric.abs()).max(1.0);
+
+        let mut max_error: f32 = 0.0;
+        for l in 0..network.layers.len() {
+            let (rows, cols) = network.layers[l].weights.shape();
+            for i in 0..rows {
+                for j in 0..cols {
+                    let original = network.layers[l].weights.rows[i].elements[j];
+                    network.layers[l].weights.rows[i].elements[j] = original + epsilon;
+                    let plus = loss(&network);
+                    network.layers[l].weights.rows[i].elements[j] = original - epsilon;
+                    let minus = loss(&network);
+                    network.layers[l].weights.rows[i].elements[j] = original;
+                    let numeric = (plus - minus) / (2.0 * epsilon);
+                    max_error = max_error.max(relative_error(weight_gradients[l].rows[i].elements[j], numeric));
+                }
+            }
+            for j in 0..network.layers[l].biases.len() {
+                let original = network.layers[l].biases.elements[j];
+                network.layers[l].biases.elements[j] = original + epsilon;
+                let plus = loss(&network);
+                network.layers[l].biases.elements[j] = original - epsilon;
+                let minus = loss(&network);
+                network.layers[l].biases.elements[j] = original;
+                let numeric = (plus - minus) / (2.0 * epsilon);
+                max_error = max_error.max(relative_error(deltas[l].elements[j], numeric));
+            }
+        }
+        max_error
+    }
+
+    /// Zeroes every weight whose magnitude is below `threshold`, and every edge
+    /// spline whose control points all are, removing those edges from the model.
+    pub fn prune(&mut self, threshold: f32) {
+        self.layers.iter_mut().for_each(|layer| layer.prune(threshold));
+    }
+
+    /// Trains the network on a single input and target, letting `optimizer`
+    /// decide how the weight and bias gradients are applied to each layer.
+    /// The edge splines and batch normalization take a plain gradient step at
+    /// the optimizer's learning rate.
+    ///
+    /// The `weight_decay` and `l1_lambda` penalties are added to the gradients
+    /// before the optimizer sees them, as `weight_decay * w` and
+    /// `l1_lambda * sign(w)`, and `lambda_entropy` takes the same step as in
+    /// `update`. Returns a `NonFinite` error like `train`.
+    #[cfg(feature = "std")]
+    pub fn train_with_optimizer(&mut self, input: Vector, target: Vector, optimizer: &mut dyn Optimizer) -> Result<(), NetworkError> {
+        let Gradients { weights, mut biases, mut splines, batch_norm, .. } = self.gradients_with_loss(input, target, &MseLoss)?;
+        let mut weight_gradients = self.tie_gradients(weights);
+        let learning_rate = optimizer.learning_rate();
+        let (weight_decay, l1_lambda, lambda_entropy) = (self.weight_decay, self.l1_lambda, self.lambda_entropy);
+        let sign = |x: f32| if x == 0.0 { 0.0 } else { x.signum() };
+        for (index, ((((layer, weight_gradient), delta), spline_gradients), batch_norm_gradients)) in
+            self.layers.iter_mut().zip(&mut weight_gradients).zip(&mut biases).zip(&mut splines).zip(&batch_norm).enumerate()
+        {
+            if self.frozen.get(index).copied().unwrap_or(false) {
+                continue;
+            }
+            if let Some(max_norm) = self.max_gradient_norm {
+                clip_gradients(weight_gradient, delta, max_norm);
+            }
+            for (gradient_row, weight_row) in weight_gradient.rows.iter_mut().zip(&layer.weights.rows) {
+                for (g, &w) in gradient_row.elements.iter_mut().zip(&weight_row.elements) {
+                    *g += weight_decay * w + l1_lambda * sign(w);
+                }
+            }
+            if l1_lambda != 0.0 {
+                for (edge_gradients, spline) in spline_gradients.iter_mut().flatten().zip(layer.splines.iter().flatten()) {
+                    edge_gradients.iter_mut().zip(&spline.control_points).for_each(|(g, &c)| *g += l1_lambda * sign(c));
+                }
+            }
+            optimizer.step(index, layer, weight_gradient, delta);
+            if lambda_entropy != 0.0 {
+                layer.entropy_step(learning_rate * lambda_entropy);
+            }
+            layer.update_splines(spline_gradients, learning_rate);
+            if let (Some(batch_norm), Some((gamma_grad, beta_grad))) = (&mut layer.batch_norm, batch_norm_gradients) {
+                batch_norm.update(gamma_grad, beta_grad, learning_rate);
+            }
+        }
+        self.sync_tied_weights();
+        match self.first_non_finite_layer() {
+            Some(layer) => Err(NetworkError::NonFinite { epoch: None, layer }),
+            None => Ok(()),
         }
-        output
     }
 
     pub fn loss(&self, input: Vector, target: Vector) -> f32 {
         let output = self.forward(input);
 
         let error = output.subtract(&target);
-        println!("error: {:?}", error);
         error.magnitude()
     }
 
+    /// Calculates the mean squared error between the output and the target.
+    ///
+    /// `backward` differentiates half the summed squared error, so training
+    /// minimizes this loss up to a constant factor.
+    pub fn mse_loss(&self, input: Vector, target: Vector) -> f32 {
+        MseLoss.value(&self.forward(input), &target)
+    }
+
+    /// Calculates the Huber loss between the output and the target, quadratic
+    /// for errors within `delta` and linear beyond, averaged over the elements.
+    pub fn huber_loss(&self, input: Vector, target: Vector, delta: f32) -> f32 {
+        huber(&self.forward(input), &target, delta)
+    }
+
+    /// Calculates the cross-entropy between the softmax of the output and a
+    /// one-hot (or probability) target.
+    pub fn cross_entropy_loss(&self, input: Vector, target: Vector) -> f32 {
+        CrossEntropyLoss.value(&self.forward(input), &target)
+    }
+
+    /// Returns the index of the largest output, i.e. the predicted class.
+    pub fn predict_class(&self, input: Vector) -> usize {
+        self.forward(input).argmax()
+    }
+
+    /// Treats a single-output network as a binary classifier and returns its
+    /// `(precision, recall, f1)`. A prediction or target is positive when its
+    /// first element is at least `threshold`. Ratios with a zero denominator
+    /// are reported as 0.
+    pub fn precision_recall_f1(&self, inputs: &[Vector], targets: &[Vector], threshold: f32) -> (f32, f32, f32) {
+        let (mut true_positives, mut false_positives, mut false_negatives) = (0, 0, 0);
+        for (input, target) in inputs.iter().zip(targets) {
+            let predicted = self.predict(input.clone()).elements[0] >= threshold;
+            let actual = target.elements[0] >= threshold;
+            match (predicted, actual) {
+                (true, true) => true_positives += 1,
+                (true, false) => false_positives += 1,
+                (false, true) => false_negatives += 1,
+                (false, false) => {}
+            }
+        }
+
+        let ratio = |numerator: f32, denominator: f32| if denominator == 0.0 { 0.0 } else { numerator / denominator };
+        let precision = ratio(true_positives as f32, (true_positives + false_positives) as f32);
+        let recall = ratio(true_positives as f32, (true_positives + false_negatives) as f32);
+        (precision, recall, ratio(2.0 * precision * recall, precision + recall))
+    }
+
+    /// Counts, for one-hot `targets`, how many samples of true class `i` are
+    /// predicted as class `j` in entry `(i, j)`. Samples whose true or
+    /// predicted class is not below `num_classes` are not counted.
+    pub fn confusion_matrix(&self, inputs: &[Vector], targets: &[Vector], num_classes: usize) -> Matrix {
+        let mut counts = Matrix::zeros(num_classes, num_classes);
+        for (input, target) in inputs.iter().zip(targets) {
+            let (actual, predicted) = (target.argmax(), self.predict_class(input.clone()));
+            if actual < num_classes && predicted < num_classes {
+                counts.rows[actual].elements[predicted] += 1.0;
+            }
+        }
+        counts
+    }
+
+    /// Returns the fraction of outputs within 0.5 of their target; see
+    /// `accuracy_with_threshold`.
     pub fn accuracy(&self, input: Vector, target: Vector) -> f32 {
+        self.accuracy_with_threshold(input, target, 0.5)
+    }
+
+    /// Returns the fraction of outputs whose absolute error is below
+    /// `threshold`: a regression-style tolerance accuracy rather than a
+    /// classification accuracy. Use `predict_class` to compare class labels.
+    pub fn accuracy_with_threshold(&self, input: Vector, target: Vector, threshold: f32) -> f32 {
         let output = self.forward(input);
         let error = output.subtract(&target);
-        let correct = error.elements.iter().filter(|&&x| x.abs() < 0.5).count() as f32;
+        let correct = error.elements.iter().filter(|&&x| x.abs() < threshold).count() as f32;
         correct / target.len() as f32
     }
 
@@ -91,87 +1107,288 @@ impl Network {
         (total_loss / inputs.len() as f32, total_accuracy / inputs.len() as f32)
     }
 
-    pub fn train_epoch(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32) {
-        for (input, target) in inputs.iter().zip(targets) {
-            self.train(input.clone(), target.clone(), learning_rate);
+    /// Calculates the coefficient of determination `1 - SS_res / SS_tot`, with
+    /// `SS_tot` measured around the mean of each output dimension.
+    ///
+    /// When every target is the same, `SS_tot` is zero and the score is 1.0
+    /// for exact predictions and 0.0 otherwise. Panics if there are no
+    /// samples.
+    pub fn r2_score(&self, inputs: &[Vector], targets: &[Vector]) -> f32 {
+        assert!(!targets.is_empty(), "R² needs at least one sample");
+        let outputs = self.predict_batch(inputs);
+        let mean = targets.iter().skip(1).fold(targets[0].clone(), |sum, target| sum.add(target).unwrap()).scalar_multiply(1.0 / targets.len() as f32);
+        let ss_res: f32 = outputs.iter().zip(targets).map(|(output, target)| output.subtract(target).map(|e| e * e).sum()).sum();
+        let ss_tot: f32 = targets.iter().map(|target| target.subtract(&mean).map(|e| e * e).sum()).sum();
+        if ss_tot == 0.0 {
+            return if ss_res == 0.0 { 1.0 } else { 0.0 };
         }
+        1.0 - ss_res / ss_tot
     }
 
-    pub fn train_epochs(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, epochs: usize) {
-        for _ in 0..epochs {
-            self.train_epoch(inputs, targets, learning_rate);
+    /// Calculates the mean absolute error over every output of every sample.
+    pub fn mae(&self, inputs: &[Vector], targets: &[Vector]) -> f32 {
+        let outputs = self.predict_batch(inputs);
+        let total: f32 = outputs.iter().zip(targets).map(|(output, target)| output.subtract(target).map(f32::abs).sum()).sum();
+        let count: usize = targets.iter().map(Vector::len).sum();
+        total / count as f32
+    }
+
+    /// Trains for one epoch, taking a `train` step on each sample and stopping
+    /// at the first step that fails. Samples are visited in order, or in a
+    /// fresh shuffled order after `with_shuffle_seed`.
+    #[cfg(feature = "std")]
+    pub fn train_epoch(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32) -> Result<(), NetworkError> {
+        self.train_epoch_with_callback(inputs, targets, learning_rate, |_| {})
+    }
+
+    /// Like `train_epoch`, but calls `on_sample(index)` after the step on each
+    /// sample, in the order the samples are visited.
+    #[cfg(feature = "std")]
+    pub fn train_epoch_with_callback(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, mut on_sample: impl FnMut(usize)) -> Result<(), NetworkError> {
+        for index in self.epoch_order(inputs.len().min(targets.len())) {
+            self.train(inputs[index].clone(), targets[index].clone(), learning_rate)?;
+            on_sample(index);
+        }
+        Ok(())
+    }
+
+    /// Returns the order in which an epoch visits `len` samples: shuffled by
+    /// the shuffle RNG if there is one, and sequential otherwise.
+    #[cfg(feature = "std")]
+    fn epoch_order(&mut self, len: usize) -> Vec<usize> {
+        use rand::seq::SliceRandom;
+        let mut indices: Vec<usize> = (0..len).collect();
+        if let Some(rng) = &mut self.shuffle_rng {
+            indices.shuffle(rng);
+        }
+        indices
+    }
+
+    /// Takes a `train` step on each sample as `data` yields it, so the dataset
+    /// never has to fit in memory. Stops at the first step that fails and
+    /// otherwise returns the number of samples trained on.
+    #[cfg(feature = "std")]
+    pub fn train_stream(&mut self, data: impl Iterator<Item = (Vector, Vector)>, learning_rate: f32) -> Result<usize, NetworkError> {
+        let mut samples = 0;
+        for (input, target) in data {
+            self.train(input, target, learning_rate)?;
+            samples += 1;
+        }
+        Ok(samples)
+    }
+
+    /// Trains epoch `epoch` of a longer run, tagging any error with it.
+    #[cfg(feature = "std")]
+    fn train_numbered_epoch(&mut self, epoch: usize, inputs: &[Vector], targets: &[Vector], learning_rate: f32) -> Result<(), NetworkError> {
+        self.train_epoch(inputs, targets, learning_rate).map_err(|e| e.in_epoch(epoch))
+    }
+
+    /// Trains for `epochs` epochs, showing a progress bar when `verbose` is set.
+    #[cfg(feature = "std")]
+    pub fn train_epochs(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, epochs: usize) -> Result<(), NetworkError> {
+        let progress_bar = if self.verbose { ProgressBar::new(epochs as u64) } else { ProgressBar::hidden() };
+        for epoch in 0..epochs {
+            self.train_numbered_epoch(epoch, inputs, targets, learning_rate)?;
+            progress_bar.inc(1);
+        }
+        progress_bar.finish_and_clear();
+        Ok(())
+    }
+
+    /// Trains for `epochs` epochs, calling `on_epoch(epoch, loss, accuracy)`
+    /// after each one with the metrics of `evaluate_batch` on the training set,
+    /// for logging or checkpointing.
+    #[cfg(feature = "std")]
+    pub fn train_epochs_with_callback(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, epochs: usize, mut on_epoch: impl FnMut(usize, f32, f32)) -> Result<(), NetworkError> {
+        for epoch in 0..epochs {
+            self.train_numbered_epoch(epoch, inputs, targets, learning_rate)?;
+            let (loss, accuracy) = self.evaluate_batch(inputs, targets);
+            on_epoch(epoch, loss, accuracy);
+        }
+        Ok(())
+    }
+
+    /// Trains for `epochs` epochs like `train_epochs_with_callback`, and
+    /// returns the loss and accuracy after every epoch for plotting or custom
+    /// stopping rules.
+    #[cfg(feature = "std")]
+    pub fn fit(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, epochs: usize) -> Result<History, NetworkError> {
+        let mut history = History::default();
+        self.train_epochs_with_callback(inputs, targets, learning_rate, epochs, |_, loss, accuracy| {
+            history.loss.push(loss);
+            history.accuracy.push(accuracy);
+        })?;
+        Ok(history)
+    }
+
+    /// Trains for `epochs` epochs, querying `schedule` for the learning rate
+    /// at the start of each epoch.
+    #[cfg(feature = "std")]
+    pub fn train_epochs_with_schedule(&mut self, inputs: &[Vector], targets: &[Vector], schedule: &LrSchedule, epochs: usize) -> Result<(), NetworkError> {
+        for epoch in 0..epochs {
+            self.train_numbered_epoch(epoch, inputs, targets, schedule.lr_at(epoch))?;
+        }
+        Ok(())
+    }
+
+    /// Trains for `epochs` epochs on the `schedule` learning rates, keeping a
+    /// snapshot of the layers whenever the `evaluate_batch` loss on the
+    /// training set improves, and restores the best snapshot at the end, or
+    /// when a step fails.
+    #[cfg(feature = "std")]
+    pub fn train_with_checkpointing(&mut self, inputs: &[Vector], targets: &[Vector], schedule: &LrSchedule, epochs: usize) -> Result<Checkpoint, NetworkError> {
+        let mut best = Checkpoint { epochs: 0, loss: self.evaluate_batch(inputs, targets).0 };
+        let mut best_layers = self.layers.clone();
+        for epoch in 0..epochs {
+            if let Err(e) = self.train_numbered_epoch(epoch, inputs, targets, schedule.lr_at(epoch)) {
+                self.layers = best_layers;
+                return Err(e);
+            }
+            let loss = self.evaluate_batch(inputs, targets).0;
+            if loss < best.loss {
+                best = Checkpoint { epochs: epoch + 1, loss };
+                best_layers = self.layers.clone();
+            }
         }
+        self.layers = best_layers;
+        Ok(best)
     }
 
-    pub fn save(&self, path: &str) {
-        let mut file = File::create(path).unwrap();
-        file.write_all(self.to_string().as_bytes()).unwrap();
+    /// Saves the network as JSON, keeping every layer field: activations,
+    /// biases, dropout, batch normalization and edge splines.
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: &str) -> Result<(), NetworkError> {
+        let json = self.to_json().map_err(|e| NetworkError::Parse(e.to_string()))?;
+        File::create(path)?.write_all(json.as_bytes())?;
+        Ok(())
     }
 
-    pub fn load(path: &str) -> Network {
-        let mut file = File::open(path).unwrap();
+    /// Loads a network written by `save`. Files in the older `to_string`
+    /// text format, which only stores weights and biases, are still read.
+    #[cfg(feature = "std")]
+    pub fn load(path: &str) -> Result<Network, NetworkError> {
+        let mut file = File::open(path)?;
         let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
+        file.read_to_string(&mut contents)?;
 
-        for line in contents.lines() {
-            println!("{}", line);
+        if contents.trim_start().starts_with('{') {
+            Network::from_json(&contents).map_err(|e| NetworkError::Parse(e.to_string()))
+        } else {
+            Network::try_from_str(&contents)
         }
+    }
 
-        Network::from_str(&contents)
+    /// Saves the network in a compact binary encoding that preserves every
+    /// `f32` exactly, wrapped in a versioned `NetworkFile`.
+    #[cfg(feature = "std")]
+    pub fn save_binary(&self, path: &str) -> Result<(), NetworkError> {
+        NetworkFile::new(self.clone()).save(path)
     }
 
-    pub fn train_until_convergence(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, max_epochs: usize, tolerance: f32) {
+    /// Loads a network written by `save_binary`. Files of another format
+    /// version are rejected with a `VersionMismatch` error.
+    #[cfg(feature = "std")]
+    pub fn load_binary(path: &str) -> Result<Network, NetworkError> {
+        NetworkFile::load(path).map(|file| file.network)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn train_until_convergence(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, max_epochs: usize, tolerance: f32) -> Result<(), NetworkError> {
         let mut epoch = 0;
         let mut prev_loss = f32::INFINITY;
         let mut loss = self.evaluate(inputs, targets).0;
         while (prev_loss - loss).abs() > tolerance && epoch < max_epochs {
             prev_loss = loss;
-            self.train_epoch(inputs, targets, learning_rate);
+            self.train_numbered_epoch(epoch, inputs, targets, learning_rate)?;
             loss = self.evaluate(inputs, targets).0;
             epoch += 1;
         }
-    }
-
-    pub fn predict_batch(&self, inputs: &[Vector]) -> Vec<Vector> {
-        inputs.iter().map(|input| self.predict(input.clone())).collect()
+        Ok(())
     }
 
     pub fn loss_batch(&self, inputs: &[Vector], targets: &[Vector]) -> f32 {
-        let total_loss: f32 = inputs.iter().zip(targets).map(|(input, target)| self.loss(input.clone(), target.clone())).sum();
+        let total_loss: f32 = self.map_samples(inputs, targets, |input, target| self.loss(input, target)).iter().sum();
         total_loss / inputs.len() as f32
     }
 
     pub fn accuracy_batch(&self, inputs: &[Vector], targets: &[Vector]) -> f32 {
-        let total_accuracy: f32 = inputs.iter().zip(targets).map(|(input, target)| self.accuracy(input.clone(), target.clone())).sum();
+        let total_accuracy: f32 = self.map_samples(inputs, targets, |input, target| self.accuracy(input, target)).iter().sum();
         total_accuracy / inputs.len() as f32
     }
 
+    /// Computes a per-sample metric, in parallel with the `parallel` feature.
+    /// The results are summed by the caller in sample order, so both paths
+    /// produce identical totals.
+    fn map_samples(&self, inputs: &[Vector], targets: &[Vector], metric: impl Fn(Vector, Vector) -> f32 + Sync) -> Vec<f32> {
+        #[cfg(feature = "parallel")]
+        return inputs.par_iter().zip(targets).map(|(input, target)| metric(input.clone(), target.clone())).collect();
+        #[cfg(not(feature = "parallel"))]
+        inputs.iter().zip(targets).map(|(input, target)| metric(input.clone(), target.clone())).collect()
+    }
+
     pub fn evaluate_batch(&self, inputs: &[Vector], targets: &[Vector]) -> (f32, f32) {
         (self.loss_batch(inputs, targets), self.accuracy_batch(inputs, targets))
     }
 
+    /// Trains the network for one epoch over shuffled mini-batches, averaging
+    /// the gradients of each batch into a single update. The final batch may
+    /// be smaller than `batch_size`. Each batch is first folded into the
+    /// running estimates of any batch normalization. The shuffle draws from
+    /// the `with_shuffle_seed` RNG if there is one.
+    #[cfg(feature = "std")]
     pub fn train_minibatch(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, batch_size: usize) {
         use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
         let mut indices: Vec<usize> = (0..inputs.len()).collect();
-        indices.shuffle(&mut rng);
+        match &mut self.shuffle_rng {
+            Some(rng) => indices.shuffle(rng),
+            None => indices.shuffle(&mut rand::thread_rng()),
+        }
 
+        let batch_norm = self.layers.iter().any(|layer| layer.batch_norm.is_some());
         for i in (0..inputs.len()).step_by(batch_size) {
-            let batch_indices = &indices[i..i + batch_size];
-            let batch_inputs: Vec<Vector> = batch_indices.iter().map(|&i| inputs[i].clone()).collect();
-            let batch_targets: Vec<Vector> = batch_indices.iter().map(|&i| targets[i].clone()).collect();
-            for (input, target) in batch_inputs.iter().zip(batch_targets) {
-                self.train(input.clone(), target.clone(), learning_rate);
+            let batch = &indices[i..(i + batch_size).min(inputs.len())];
+            if batch_norm {
+                self.update_batch_norm_statistics(&Matrix::new(batch.iter().map(|&index| inputs[index].clone()).collect()));
+            }
+            for &index in batch {
+                self.accumulate_gradients(inputs[index].clone(), targets[index].clone());
             }
+            self.apply_accumulated(learning_rate);
         }
     }
 
+    /// Adds the gradients of one sample to the accumulation buffers without
+    /// updating the parameters, so that several micro-batches can be combined
+    /// into one `apply_accumulated` step.
+    pub fn accumulate_gradients(&mut self, input: Vector, target: Vector) {
+        let gradients = self.gradients(input, target);
+        match &mut self.accumulated {
+            Some((totals, count)) => {
+                totals.accumulate(&gradients);
+                *count += 1;
+            }
+            None => self.accumulated = Some((gradients, 1)),
+        }
+    }
+
+    /// Takes one `update` step with the average of the accumulated gradients
+    /// and clears the buffers. Does nothing if no gradients were accumulated.
+    pub fn apply_accumulated(&mut self, learning_rate: f32) {
+        if let Some((mut totals, count)) = self.accumulated.take() {
+            totals.scale(1.0 / count as f32);
+            let learning_rates = vec![learning_rate; self.layers.len()];
+            self.apply_gradients(&totals, &learning_rates);
+        }
+    }
+
+    #[cfg(feature = "std")]
     pub fn train_minibatches(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, batch_size: usize, epochs: usize) {
         for _ in 0..epochs {
             self.train_minibatch(inputs, targets, learning_rate, batch_size);
         }
     }
 
+    #[cfg(feature = "std")]
     pub fn train_minibatches_until_convergence(&mut self, inputs: &[Vector], targets: &[Vector], learning_rate: f32, batch_size: usize, max_epochs: usize, tolerance: f32) {
         let mut epoch = 0;
         let mut prev_loss = f32::INFINITY;
@@ -184,37 +1401,31 @@ impl Network {
         }
     }
 
-    pub fn train_minibatches_until_convergence_with_validation(&mut self, inputs: &[Vector], targets: &[Vector], validation_inputs: &[Vector], validation_targets: &[Vector], learning_rate: f32, batch_size: usize, max_epochs: usize, tolerance: f32) {
-        let mut epoch = 0;
-        let mut prev_loss = f32::INFINITY;
-        let mut loss = self.evaluate_batch(inputs, targets).0;
-        let mut validation_loss = self.evaluate_batch(validation_inputs, validation_targets).0;
-        while (prev_loss - loss).abs() > tolerance && epoch < max_epochs {
-            prev_loss = loss;
-            self.train_minibatch(inputs, targets, learning_rate, batch_size);
-            loss = self.evaluate_batch(inputs, targets).0;
-            validation_loss = self.evaluate_batch(validation_inputs, validation_targets).0;
-            epoch += 1;
-        }
+    /// Like `train_minibatches_until_convergence`. The validation set does not
+    /// affect training; use `train_minibatches_until_convergence_with_validation_and_early_stopping`
+    /// to stop on it.
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn train_minibatches_until_convergence_with_validation(&mut self, inputs: &[Vector], targets: &[Vector], _validation_inputs: &[Vector], _validation_targets: &[Vector], learning_rate: f32, batch_size: usize, max_epochs: usize, tolerance: f32) {
+        self.train_minibatches_until_convergence(inputs, targets, learning_rate, batch_size, max_epochs, tolerance);
     }
 
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments)]
     pub fn train_minibatches_until_convergence_with_validation_and_early_stopping(&mut self, inputs: &[Vector], targets: &[Vector], validation_inputs: &[Vector], validation_targets: &[Vector], learning_rate: f32, batch_size: usize, max_epochs: usize, tolerance: f32, patience: usize) {
         let mut epoch = 0;
         let mut prev_loss = f32::INFINITY;
         let mut loss = self.evaluate_batch(inputs, targets).0;
-        let mut validation_loss = self.evaluate_batch(validation_inputs, validation_targets).0;
-        let mut best_loss = validation_loss;
-        let mut best_epoch = 0;
+        let mut best_loss = self.evaluate_batch(validation_inputs, validation_targets).0;
         let mut early_stopping = false;
         let mut patience_counter = 0;
         while (prev_loss - loss).abs() > tolerance && epoch < max_epochs && !early_stopping {
             prev_loss = loss;
             self.train_minibatch(inputs, targets, learning_rate, batch_size);
             loss = self.evaluate_batch(inputs, targets).0;
-            validation_loss = self.evaluate_batch(validation_inputs, validation_targets).0;
+            let validation_loss = self.evaluate_batch(validation_inputs, validation_targets).0;
             if validation_loss < best_loss {
                 best_loss = validation_loss;
-                best_epoch = epoch;
                 patience_counter = 0;
             } else {
                 patience_counter += 1;
@@ -226,13 +1437,76 @@ impl Network {
         }
     }
 
+    /// Trains one epoch at a time until the validation loss has not improved
+    /// for `patience` consecutive epochs or `max_epochs` is reached, then
+    /// restores the layers that achieved the best validation loss.
+    ///
+    /// Returns the number of epochs that were trained.
+    #[cfg(feature = "std")]
+    pub fn train_with_early_stopping(&mut self, train: (&[Vector], &[Vector]), val: (&[Vector], &[Vector]), learning_rate: f32, max_epochs: usize, patience: usize) -> Result<usize, NetworkError> {
+        let mut best_loss = self.evaluate_batch(val.0, val.1).0;
+        let mut best_layers = self.layers.clone();
+        let mut patience_counter = 0;
+        let mut epoch = 0;
+        while epoch < max_epochs {
+            self.train_numbered_epoch(epoch, train.0, train.1, learning_rate)?;
+            epoch += 1;
+            let validation_loss = self.evaluate_batch(val.0, val.1).0;
+            if validation_loss < best_loss {
+                best_loss = validation_loss;
+                best_layers = self.layers.clone();
+                patience_counter = 0;
+            } else {
+                patience_counter += 1;
+                if patience_counter >= patience {
+                    break;
+                }
+            }
+        }
+        self.layers = best_layers;
+        Ok(epoch)
+    }
+
+    #[cfg(feature = "std")]
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Network {
-        let layers: Vec<Layer> = s.split("Layer").map(|s| Layer::from_str(s)).collect();
-        Network::new(layers)
+        Network::try_from_str(s).expect("Invalid network string")
     }
 
+    /// Parses a network from the `to_string` format, reporting malformed
+    /// layers instead of panicking.
+    #[cfg(feature = "std")]
+    pub fn try_from_str(s: &str) -> Result<Network, NetworkError> {
+        let layers = s
+            .split("Layer")
+            .map(str::trim)
+            .filter(|fragment| !fragment.is_empty())
+            .map(Layer::try_from_str)
+            .collect::<Result<Vec<Layer>, String>>()
+            .map_err(NetworkError::Parse)?;
+        Ok(Network::new(layers))
+    }
+
+    /// Serializes for persistence; use `Display` for human-readable output.
+    ///
+    /// Each layer is written after a `Layer` delimiter line. Only weights and
+    /// biases are stored; `save` and `to_json` keep every layer field.
+    #[cfg(feature = "std")]
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
-        self.layers.iter().map(|layer| layer.to_str()).collect::<Vec<String>>().join("\n")
+        self.layers.iter().map(|layer| format!("Layer\n{}", layer.to_str())).collect::<Vec<String>>().join("\n")
+    }
+
+    /// Serializes the network, including any edge splines, to JSON.
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserializes a network produced by `to_json`.
+    #[cfg(feature = "std")]
+    pub fn from_json(s: &str) -> Result<Network, serde_json::Error> {
+        serde_json::from_str(s)
     }
 
     pub fn update_weights(&mut self, learning_rate: f32) {
@@ -251,15 +1525,36 @@ impl Network {
         error.elementwise_multiply(gradient)
     }
 
-    pub fn biases(&self) -> Vec<Vector> {
-        self.layers.iter().map(|layer| layer.biases.clone()).collect()
+    /// Refines the grid of every edge spline in the network to `new_grid_size`
+    /// intervals, preserving the learned functions.
+    pub fn refine_grid(&mut self, new_grid_size: usize) {
+        for layer in self.layers.iter_mut() {
+            layer.refine_grid(new_grid_size);
+        }
     }
 
-    pub fn weights(&self) -> Vec<Matrix> {
-        self.layers.iter().map(|layer| layer.weights.clone()).collect()
+    /// Replaces every edge with its best-fitting elementary function, returning
+    /// one formula per edge for each layer, ordered by input and then output.
+    /// The formula for the edge from input `i` is written in the variable `x{i}`.
+    pub fn to_symbolic(&self) -> Vec<Vec<String>> {
+        self.layers
+            .iter()
+            .map(|layer| {
+                let (input_dim, output_dim) = (layer.input_dim(), layer.output_dim());
+                (0..input_dim)
+                    .flat_map(|i| (0..output_dim).map(move |j| (i, j)))
+                    .map(|(i, j)| {
+                        let var = format!("x{}", i);
+                        layer
+                            .fit_symbolic_edge(i, j, &SymbolicFn::ALL)
+                            .map_or_else(|| "0".to_string(), |fit| fit.formula(&var))
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
-    pub fn weight_gradients(&self, input: &Vector, output: &Vector, gradient: &Vector) -> Matrix {
+    pub fn weight_gradients(&self, input: &Vector, gradient: &Vector) -> Matrix {
         let cols = self.layers.last().unwrap().weights.col_count();
         let rows = self.layers.last().unwrap().weights.row_count();
         let mut weight_gradients = Matrix::zeros(cols, rows);
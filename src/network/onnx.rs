@@ -0,0 +1,165 @@
+use crate::network::{Network, NetworkError};
+use crate::utils::activations::Activation;
+use std::fs::File;
+use std::io::Write;
+
+/// The ONNX IR version matching opset 13.
+const IR_VERSION: u64 = 7;
+const OPSET_VERSION: u64 = 13;
+/// The `TensorProto.DataType` code of `float`.
+const FLOAT: u64 = 1;
+
+impl Network {
+    /// Writes the network to `path` as an ONNX model for opset 13; see
+    /// `to_onnx`.
+    pub fn export_onnx(&self, path: &str) -> Result<(), NetworkError> {
+        let bytes = self.to_onnx()?;
+        File::create(path)?.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Encodes the network as an ONNX model for opset 13, with a float
+    /// `input` of shape `[N, input_dim]` and an `output` of shape
+    /// `[N, output_dim]`.
+    ///
+    /// Every layer becomes a `MatMul` by its weights, an `Add` of its biases
+    /// unless it is bias-free, its activation, and an `Add` of its input if it
+    /// is residual. The `Identity`, `Relu`, `Sigmoid` and `Tanh` activations
+    /// are supported; `Gelu` has no opset 13 operator. Dropout is an
+    /// inference no-op and is left out. Layers with edge splines or batch
+    /// normalization cannot be exported.
+    pub fn to_onnx(&self) -> Result<Vec<u8>, NetworkError> {
+        let mut graph = Vec::new();
+        let mut current = "input".to_string();
+        for (i, layer) in self.layers.iter().enumerate() {
+            if !layer.splines.is_empty() {
+                return Err(NetworkError::Export(format!("Layer {} has edge splines, which ONNX export does not support", i)));
+            }
+            if layer.batch_norm.is_some() {
+                return Err(NetworkError::Export(format!("Layer {} has batch normalization, which ONNX export does not support", i)));
+            }
+            let activation = match layer.activation {
+                Activation::Identity => None,
+                Activation::Relu => Some("Relu"),
+                Activation::Sigmoid => Some("Sigmoid"),
+                Activation::Tanh => Some("Tanh"),
+                Activation::Gelu => return Err(NetworkError::Export(format!("Layer {} uses Gelu, which opset {} does not provide", i, OPSET_VERSION))),
+            };
+
+            let layer_input = current.clone();
+            let weights: Vec<f32> = layer.weights.rows.iter().flat_map(|row| row.elements.iter().copied()).collect();
+            let weight_name = format!("layer{}.weight", i);
+            message(&mut graph, 5, &tensor(&weight_name, &[layer.input_dim(), layer.output_dim()], &weights));
+            current = node(&mut graph, "MatMul", &[&layer_input, &weight_name], &format!("layer{}.matmul", i));
+
+            if layer.use_bias {
+                let bias_name = format!("layer{}.bias", i);
+                message(&mut graph, 5, &tensor(&bias_name, &[layer.output_dim()], &layer.biases.elements));
+                current = node(&mut graph, "Add", &[&current, &bias_name], &format!("layer{}.add", i));
+            }
+            if let Some(op_type) = activation {
+                current = node(&mut graph, op_type, &[&current], &format!("layer{}.{}", i, op_type.to_lowercase()));
+            }
+            if layer.residual {
+                current = node(&mut graph, "Add", &[&current, &layer_input], &format!("layer{}.residual", i));
+            }
+        }
+        // Rename the last value to `output`, or pass the input through for an
+        // empty network.
+        node_named(&mut graph, "Identity", &[&current], "output", "output");
+
+        let input_dim = self.layers.first().map_or(0, |layer| layer.input_dim());
+        let output_dim = self.layers.last().map_or(input_dim, |layer| layer.output_dim());
+        string(&mut graph, 2, "kan");
+        message(&mut graph, 11, &value_info("input", input_dim));
+        message(&mut graph, 12, &value_info("output", output_dim));
+
+        let mut opset = Vec::new();
+        string(&mut opset, 1, "");
+        varint_field(&mut opset, 2, OPSET_VERSION);
+
+        let mut model = Vec::new();
+        varint_field(&mut model, 1, IR_VERSION);
+        string(&mut model, 2, "kan");
+        string(&mut model, 3, env!("CARGO_PKG_VERSION"));
+        message(&mut model, 7, &graph);
+        message(&mut model, 8, &opset);
+        Ok(model)
+    }
+}
+
+/// Appends a `NodeProto` to `graph` and returns the name of its output.
+fn node(graph: &mut Vec<u8>, op_type: &str, inputs: &[&str], name: &str) -> String {
+    node_named(graph, op_type, inputs, name, name);
+    name.to_string()
+}
+
+fn node_named(graph: &mut Vec<u8>, op_type: &str, inputs: &[&str], output: &str, name: &str) {
+    let mut node = Vec::new();
+    for input in inputs {
+        string(&mut node, 1, input);
+    }
+    string(&mut node, 2, output);
+    string(&mut node, 3, name);
+    string(&mut node, 4, op_type);
+    message(graph, 1, &node);
+}
+
+/// Encodes a float `TensorProto` initializer.
+fn tensor(name: &str, dims: &[usize], values: &[f32]) -> Vec<u8> {
+    let mut tensor = Vec::new();
+    for &dim in dims {
+        varint_field(&mut tensor, 1, dim as u64);
+    }
+    varint_field(&mut tensor, 2, FLOAT);
+    string(&mut tensor, 8, name);
+    let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    message(&mut tensor, 9, &raw);
+    tensor
+}
+
+/// Encodes the `ValueInfoProto` of a float tensor of shape `[N, dim]`.
+fn value_info(name: &str, dim: usize) -> Vec<u8> {
+    let mut batch = Vec::new();
+    string(&mut batch, 2, "N");
+    let mut features = Vec::new();
+    varint_field(&mut features, 1, dim as u64);
+    let mut shape = Vec::new();
+    message(&mut shape, 1, &batch);
+    message(&mut shape, 1, &features);
+
+    let mut tensor_type = Vec::new();
+    varint_field(&mut tensor_type, 1, FLOAT);
+    message(&mut tensor_type, 2, &shape);
+    let mut type_proto = Vec::new();
+    message(&mut type_proto, 1, &tensor_type);
+
+    let mut info = Vec::new();
+    string(&mut info, 1, name);
+    message(&mut info, 2, &type_proto);
+    info
+}
+
+fn varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn varint_field(out: &mut Vec<u8>, field: u64, value: u64) {
+    varint(out, field << 3);
+    varint(out, value);
+}
+
+/// Appends a length-delimited field: an embedded message, string or bytes.
+fn message(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    varint(out, (field << 3) | 2);
+    varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn string(out: &mut Vec<u8>, field: u64, value: &str) {
+    message(out, field, value.as_bytes());
+}
@@ -0,0 +1,140 @@
+use crate::data_structures::{Layer, Matrix, Vector};
+use std::collections::HashMap;
+
+/// An update rule that applies the gradients computed by `Network::backward`
+/// to the parameters of a layer.
+pub trait Optimizer {
+    /// Updates the layer at position `index` in the network using its weight
+    /// and bias gradients.
+    fn step(&mut self, index: usize, layer: &mut Layer, weight_grad: &Matrix, bias_grad: &Vector);
+
+    /// The step size `Network::train_with_optimizer` uses for the parameters
+    /// outside `step`: edge spline control points and batch normalization.
+    fn learning_rate(&self) -> f32;
+}
+
+/// Stochastic gradient descent, optionally with momentum.
+///
+/// With a nonzero `momentum`, each layer keeps a velocity `v = momentum * v + grad`
+/// and steps along `v` instead of the raw gradient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sgd {
+    pub learning_rate: f32,
+    pub momentum: f32,
+    velocities: HashMap<usize, (Matrix, Vector)>,
+}
+
+impl Sgd {
+    pub fn new(learning_rate: f32) -> Self {
+        Sgd { learning_rate, momentum: 0.0, velocities: HashMap::new() }
+    }
+
+    /// Sets the momentum coefficient, typically around 0.9.
+    pub fn with_momentum(mut self, momentum: f32) -> Self {
+        self.momentum = momentum;
+        self
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, index: usize, layer: &mut Layer, weight_grad: &Matrix, bias_grad: &Vector) {
+        if self.momentum == 0.0 {
+            layer.update(weight_grad, bias_grad, self.learning_rate);
+            return;
+        }
+
+        let momentum = self.momentum;
+        let (weight_velocity, bias_velocity) = self
+            .velocities
+            .entry(index)
+            .or_insert_with(|| (Matrix::zeros(weight_grad.row_count(), weight_grad.col_count()), Vector::zeros(bias_grad.len())));
+        weight_velocity.scalar_multiply_mut(momentum);
+        weight_velocity.add_mut(weight_grad).unwrap();
+        *bias_velocity = bias_velocity.scalar_multiply(momentum).add(bias_grad).unwrap();
+        layer.update(weight_velocity, bias_velocity, self.learning_rate);
+    }
+
+    fn learning_rate(&self) -> f32 {
+        self.learning_rate
+    }
+}
+
+/// The Adam optimizer, keeping bias-corrected first and second moment
+/// estimates for every parameter of every layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adam {
+    pub learning_rate: f32,
+    pub beta1: f32,
+    pub beta2: f32,
+    pub epsilon: f32,
+    moments: HashMap<usize, Moments>,
+}
+
+/// The moment buffers of a single layer.
+#[derive(Debug, Clone, PartialEq)]
+struct Moments {
+    steps: i32,
+    weights: (Matrix, Matrix),
+    biases: (Vector, Vector),
+}
+
+impl Moments {
+    fn new(weight_grad: &Matrix, bias_grad: &Vector) -> Self {
+        let (rows, cols) = weight_grad.shape();
+        Moments {
+            steps: 0,
+            weights: (Matrix::zeros(rows, cols), Matrix::zeros(rows, cols)),
+            biases: (Vector::zeros(bias_grad.len()), Vector::zeros(bias_grad.len())),
+        }
+    }
+}
+
+impl Adam {
+    /// Creates an Adam optimizer with the usual defaults `beta1 = 0.9`,
+    /// `beta2 = 0.999` and `epsilon = 1e-8`.
+    pub fn new(learning_rate: f32) -> Self {
+        Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+            moments: HashMap::new(),
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, index: usize, layer: &mut Layer, weight_grad: &Matrix, bias_grad: &Vector) {
+        let (learning_rate, beta1, beta2, epsilon) = (self.learning_rate, self.beta1, self.beta2, self.epsilon);
+        let moments = self.moments.entry(index).or_insert_with(|| Moments::new(weight_grad, bias_grad));
+        moments.steps += 1;
+        let t = moments.steps;
+
+        let adam_step = |m: &mut f32, v: &mut f32, gradient: f32| {
+            *m = beta1 * *m + (1.0 - beta1) * gradient;
+            *v = beta2 * *v + (1.0 - beta2) * gradient * gradient;
+            let m_hat = *m / (1.0 - beta1.powi(t));
+            let v_hat = *v / (1.0 - beta2.powi(t));
+            learning_rate * m_hat / (v_hat.sqrt() + epsilon)
+        };
+
+        let (m, v) = &mut moments.weights;
+        for (i, gradient_row) in weight_grad.rows.iter().enumerate() {
+            for (j, &gradient) in gradient_row.elements.iter().enumerate() {
+                layer.weights.rows[i].elements[j] -= adam_step(&mut m.rows[i].elements[j], &mut v.rows[i].elements[j], gradient);
+            }
+        }
+
+        if !layer.use_bias {
+            return;
+        }
+        let (m, v) = &mut moments.biases;
+        for (j, &gradient) in bias_grad.elements.iter().enumerate() {
+            layer.biases.elements[j] -= adam_step(&mut m.elements[j], &mut v.elements[j], gradient);
+        }
+    }
+
+    fn learning_rate(&self) -> f32 {
+        self.learning_rate
+    }
+}
@@ -0,0 +1,89 @@
+use crate::prelude::*;
+use crate::data_structures::Vector;
+use crate::network::{Network, NetworkError};
+use crate::utils::activations::Activation;
+use serde::{Deserialize, Serialize};
+use core::mem::size_of;
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float as _;
+
+/// A network whose weights are stored as `i8`, for deployment where memory
+/// matters more than the last digits of precision. Produced by
+/// `Network::quantize_int8`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedNetwork {
+    pub layers: Vec<QuantizedLayer>,
+}
+
+/// A dense layer with symmetric per-layer int8 weights: each weight is
+/// approximately `scale * weights[i * output_dim + j]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedLayer {
+    pub input_dim: usize,
+    pub output_dim: usize,
+    /// The weights in row-major order, one row per input.
+    pub weights: Vec<i8>,
+    pub scale: f32,
+    /// The biases, kept in `f32`, or empty for a bias-free layer.
+    pub biases: Vec<f32>,
+    pub activation: Activation,
+    pub residual: bool,
+}
+
+impl Network {
+    /// Quantizes every layer's weights to `i8` with the scale
+    /// `max_abs / 127`, keeping biases in `f32`. Dropout is an inference
+    /// no-op and is left out. Layers with edge splines or batch
+    /// normalization cannot be quantized.
+    pub fn quantize_int8(&self) -> Result<QuantizedNetwork, NetworkError> {
+        let mut layers = Vec::with_capacity(self.layers.len());
+        for (i, layer) in self.layers.iter().enumerate() {
+            if !layer.splines.is_empty() {
+                return Err(NetworkError::Export(format!("Layer {} has edge splines, which int8 quantization does not support", i)));
+            }
+            if layer.batch_norm.is_some() {
+                return Err(NetworkError::Export(format!("Layer {} has batch normalization, which int8 quantization does not support", i)));
+            }
+
+            let scale = layer.weights.max_abs() / 127.0;
+            let quantize = |w: f32| if scale > 0.0 { (w / scale).round().clamp(-127.0, 127.0) as i8 } else { 0 };
+            layers.push(QuantizedLayer {
+                input_dim: layer.input_dim(),
+                output_dim: layer.output_dim(),
+                weights: layer.weights.rows.iter().flat_map(|row| row.elements.iter().map(|&w| quantize(w))).collect(),
+                scale,
+                biases: if layer.use_bias { layer.biases.elements.clone() } else { Vec::new() },
+                activation: layer.activation,
+                residual: layer.residual,
+            });
+        }
+        Ok(QuantizedNetwork { layers })
+    }
+}
+
+impl QuantizedNetwork {
+    /// Runs the forward pass, accumulating each output over the integer
+    /// weights and dequantizing the sum with the layer scale.
+    pub fn forward_quantized(&self, input: Vector) -> Vector {
+        let mut output = input;
+        for layer in &self.layers {
+            assert!(output.len() == layer.input_dim, "Expected an input of dimension {} for the layer, got {}", layer.input_dim, output.len());
+            let z = Vector::new(
+                (0..layer.output_dim)
+                    .map(|j| {
+                        let sum: f32 = output.elements.iter().enumerate().map(|(i, &x)| x * f32::from(layer.weights[i * layer.output_dim + j])).sum();
+                        layer.scale * sum + layer.biases.get(j).copied().unwrap_or(0.0)
+                    })
+                    .collect(),
+            );
+            let activated = layer.activation.apply(&z);
+            output = if layer.residual { activated.add(&output).unwrap() } else { activated };
+        }
+        output
+    }
+
+    /// Returns the number of bytes taken by the weights, scales and biases.
+    pub fn size_bytes(&self) -> usize {
+        self.layers.iter().map(|layer| layer.weights.len() * size_of::<i8>() + (1 + layer.biases.len()) * size_of::<f32>()).sum()
+    }
+}
@@ -0,0 +1,38 @@
+/// A learning rate that varies with the training epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LrSchedule {
+    /// The same rate for every epoch.
+    Constant(f32),
+    /// `initial * gamma^(epoch / step_size)`: the rate is multiplied by `gamma`
+    /// every `step_size` epochs.
+    StepDecay { initial: f32, gamma: f32, step_size: usize },
+    /// `initial * gamma^epoch`: the rate is multiplied by `gamma` every epoch.
+    ExponentialDecay { initial: f32, gamma: f32 },
+    /// SGDR cosine annealing with warm restarts: at epoch `t` of each cycle of
+    /// `period` epochs the rate is `lr_min + (lr_max - lr_min) (1 + cos(π t / period)) / 2`,
+    /// falling from `lr_max` towards `lr_min` and jumping back to `lr_max`
+    /// when the next cycle starts.
+    CosineAnnealing { lr_max: f32, lr_min: f32, period: usize },
+}
+
+impl LrSchedule {
+    /// Returns the learning rate to use for `epoch`, counting from zero.
+    pub fn lr_at(&self, epoch: usize) -> f32 {
+        match *self {
+            LrSchedule::Constant(learning_rate) => learning_rate,
+            LrSchedule::StepDecay { initial, gamma, step_size } => initial * gamma.powi((epoch / step_size.max(1)) as i32),
+            LrSchedule::ExponentialDecay { initial, gamma } => initial * gamma.powi(epoch as i32),
+            LrSchedule::CosineAnnealing { lr_max, lr_min, period } => {
+                let period = period.max(1);
+                let progress = (epoch % period) as f32 / period as f32;
+                lr_min + (lr_max - lr_min) * (1.0 + (std::f32::consts::PI * progress).cos()) / 2.0
+            }
+        }
+    }
+}
+
+impl From<f32> for LrSchedule {
+    fn from(learning_rate: f32) -> Self {
+        LrSchedule::Constant(learning_rate)
+    }
+}
@@ -0,0 +1,7 @@
+//! The `alloc` items that the `std` prelude provides, imported explicitly by
+//! the modules that also build without `std`.
+
+pub(crate) use alloc::format;
+pub(crate) use alloc::string::{String, ToString};
+pub(crate) use alloc::vec;
+pub(crate) use alloc::vec::Vec;
@@ -0,0 +1,41 @@
+use crate::data_structures::{Layer, Matrix, Vector};
+use crate::network::Network;
+use crate::utils::activations::Activation;
+
+#[test]
+fn test_relu_and_derivative() {
+    let v = Vector::new(vec![-2.0, 0.0, 3.0]);
+    assert_eq!(Activation::Relu.apply(&v), Vector::new(vec![0.0, 0.0, 3.0]));
+    assert_eq!(Activation::Relu.derivative(&v), Vector::new(vec![0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn test_activation_derivatives_match_finite_differences() {
+    let h: f32 = 1e-3;
+    for activation in [Activation::Identity, Activation::Sigmoid, Activation::Tanh, Activation::Gelu] {
+        for x in [-1.5, -0.3, 0.4, 2.0] {
+            let numeric = (activation.apply(&Vector::new(vec![x + h])).elements[0]
+                - activation.apply(&Vector::new(vec![x - h])).elements[0])
+                / (2.0 * h);
+            let analytic = activation.derivative(&Vector::new(vec![x])).elements[0];
+            assert!((numeric - analytic).abs() < 1e-2, "{:?} at {}: {} vs {}", activation, x, numeric, analytic);
+        }
+    }
+}
+
+#[test]
+fn test_layer_applies_activation() {
+    let layer = Layer::new(Matrix::from_vec(vec![vec![1.0, -1.0]]), Vector::zeros(2)).with_activation(Activation::Relu);
+    assert_eq!(layer.forward(&Vector::new(vec![2.0])), Vector::new(vec![2.0, 0.0]));
+}
+
+#[test]
+fn test_backward_masks_inactive_relu_units() {
+    let network = Network::new(vec![
+        Layer::new(Matrix::from_vec(vec![vec![1.0, -1.0]]), Vector::zeros(2)).with_activation(Activation::Relu),
+    ]);
+    let (weight_gradients, deltas) = network.backward(Vector::new(vec![2.0]), Vector::new(vec![0.0, 1.0]));
+    // The second unit is inactive, so no gradient reaches its weights.
+    assert_eq!(deltas[0], Vector::new(vec![2.0, 0.0]));
+    assert_eq!(weight_gradients[0], Matrix::from_vec(vec![vec![4.0, 0.0]]));
+}
@@ -0,0 +1,107 @@
+use crate::data::{cross_validate, load_csv, one_hot, one_hot_batch, parse_csv, Dataset, Normalizer};
+use crate::data_structures::{Layer, Matrix, Vector};
+use crate::network::{Network, NetworkError};
+
+fn dataset(len: usize) -> Dataset {
+    let inputs = (0..len).map(|i| Vector::new(vec![i as f32])).collect();
+    let targets = (0..len).map(|i| Vector::new(vec![2.0 * i as f32])).collect();
+    Dataset::new(inputs, targets).unwrap()
+}
+
+#[test]
+fn test_dataset_rejects_mismatched_lengths() {
+    assert!(Dataset::new(vec![Vector::zeros(1)], vec![]).is_err());
+}
+
+#[test]
+fn test_dataset_shuffle_keeps_pairs() {
+    let mut data = dataset(20);
+    data.shuffle();
+    assert_eq!(data.len(), 20);
+    for (input, target) in data.inputs.iter().zip(&data.targets) {
+        assert_eq!(target.elements[0], 2.0 * input.elements[0]);
+    }
+}
+
+#[test]
+fn test_train_test_split() {
+    let data = dataset(10).with_seed(7);
+    let (train, test) = data.train_test_split(0.8);
+    assert_eq!(train.len(), 8);
+    assert_eq!(test.len(), 2);
+
+    let mut seen: Vec<f32> = train.inputs.iter().chain(&test.inputs).map(|v| v.elements[0]).collect();
+    seen.sort_by(f32::total_cmp);
+    assert_eq!(seen, (0..10).map(|i| i as f32).collect::<Vec<_>>());
+
+    assert_eq!(data.train_test_split(0.8), (train, test));
+}
+
+#[test]
+fn test_load_csv_fixture() {
+    let (inputs, targets) = load_csv(concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/fixtures/xor.csv"), 1).unwrap();
+    assert_eq!(inputs.len(), 4);
+    assert_eq!(inputs[1], Vector::new(vec![0.0, 1.0]));
+    assert_eq!(targets, vec![Vector::new(vec![0.0]), Vector::new(vec![1.0]), Vector::new(vec![1.0]), Vector::new(vec![0.0])]);
+}
+
+#[test]
+fn test_parse_csv_errors() {
+    assert!(matches!(load_csv("does/not/exist.csv", 1), Err(NetworkError::Io(_))));
+
+    let error = parse_csv("1,2,3\n4,x,6\n", 1).unwrap_err().to_string();
+    assert!(error.contains("Row 2"), "{}", error);
+    let error = parse_csv("1,2,3\n4,5\n", 1).unwrap_err().to_string();
+    assert_eq!(error, "Parse error: Row 2 has 2 columns but the first row has 3");
+    assert!(parse_csv("1,2\n", 2).is_err());
+}
+
+#[test]
+fn test_dataset_folds() {
+    let data = dataset(10);
+    let sizes: Vec<usize> = (0..4).map(|fold| data.fold(4, fold).1.len()).collect();
+    assert_eq!(sizes, vec![3, 3, 2, 2]);
+
+    let (train, holdout) = data.fold(4, 1);
+    assert_eq!(train.len(), 7);
+    assert_eq!(holdout.inputs, vec![Vector::new(vec![3.0]), Vector::new(vec![4.0]), Vector::new(vec![5.0])]);
+}
+
+#[test]
+fn test_cross_validate() {
+    let results = cross_validate(|| Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::zeros(1))]), &dataset(8), 4, 0.001, 10).unwrap();
+    assert_eq!(results.len(), 4);
+    assert!(results.iter().all(|(loss, accuracy)| loss.is_finite() && (0.0..=1.0).contains(accuracy)));
+}
+
+#[test]
+fn test_dataset_normalize() {
+    let inputs: Vec<Vector> = (0..10).map(|i| Vector::new(vec![i as f32 * 100.0, 0.5 - i as f32 * 0.01, 3.0])).collect();
+    let targets = vec![Vector::zeros(1); 10];
+    let mut data = Dataset::new(inputs.clone(), targets).unwrap();
+
+    let normalizer = data.normalize();
+    let stats = Normalizer::fit(&data.inputs);
+    assert!(stats.means.elements.iter().all(|m| m.abs() < 1e-4));
+    assert!(stats.stds.elements[..2].iter().all(|s| (s - 1.0).abs() < 1e-4));
+    // The constant dimension is centered but not scaled.
+    assert_eq!(stats.stds.elements[2], 0.0);
+
+    let held_out = Vector::new(vec![250.0, 0.47, 3.0]);
+    let expected = Vector::new(vec![(250.0 - 450.0) / normalizer.stds.elements[0], (0.47 - 0.455) / normalizer.stds.elements[1], 0.0]);
+    let transformed = normalizer.apply(&held_out);
+    assert!(transformed.elements.iter().zip(&expected.elements).all(|(a, b)| (a - b).abs() < 1e-3));
+    assert_eq!(normalizer.apply(&inputs[3]), data.inputs[3]);
+}
+
+#[test]
+fn test_one_hot() {
+    assert_eq!(one_hot(2, 4), Vector::new(vec![0.0, 0.0, 1.0, 0.0]));
+    assert_eq!(one_hot_batch(&[1, 0], 2), vec![Vector::new(vec![0.0, 1.0]), Vector::new(vec![1.0, 0.0])]);
+}
+
+#[test]
+#[should_panic(expected = "Label 3 out of range for 3 classes")]
+fn test_one_hot_rejects_out_of_range_label() {
+    one_hot(3, 3);
+}
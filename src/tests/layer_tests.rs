@@ -1,9 +1,11 @@
 
 use crate::data_structures::{Layer, Matrix, Vector};
+use crate::network::Network;
+use crate::utils::activations::Activation;
 
 #[test]
 fn test_new_layer() {
-    let weights = Matrix::ones(2, 3);
+    let weights: Matrix = Matrix::ones(2, 3);
     let biases = Vector::zeros(3);
     let layer = Layer::new(weights.clone(), biases.clone());
     assert_eq!(layer.weights, weights);
@@ -20,6 +22,15 @@ fn test_random_layer() {
     assert_eq!(layer.biases.len(), output_size);
 }
 
+#[test]
+fn test_kan_layer() {
+    let layer = Layer::kan(2, 3, 5, 3);
+    assert_eq!(layer.splines.len(), 2);
+    assert!(layer.splines.iter().all(|edges| edges.len() == 3));
+    assert_eq!(layer.splines[0][0].control_points.len(), 8);
+    assert_eq!(layer.forward(&Vector::new(vec![0.5, -0.5])).len(), 3);
+}
+
 // #[test]
 // fn test_forward() {
 //     let weights = Matrix::from_vec(vec![vec![1.0, 2.0]]);
@@ -64,3 +75,101 @@ fn test_random_layer() {
 //     assert_eq!(layer.weights, Matrix::from_vec(vec![vec![0.9, 1.8], vec![2.7, 3.6]]));
 //     assert_eq!(layer.biases, Vector::from_vec(vec![0.8, 1.8]));
 // }
+
+fn weight_variance(layer: &Layer) -> f32 {
+    let count = (layer.weights.row_count() * layer.weights.col_count()) as f32;
+    let mean = layer.weights.sum() / count;
+    layer.weights.map(|w| (w - mean).powi(2)).sum() / count
+}
+
+#[test]
+fn test_xavier_layer() {
+    let layer = Layer::new_xavier(300, 500);
+    assert_eq!(layer.weights.shape(), (300, 500));
+    assert_eq!(layer.biases, Vector::zeros(500));
+    let expected = 2.0 / 800.0;
+    assert!((weight_variance(&layer) - expected).abs() < 0.05 * expected);
+}
+
+#[test]
+fn test_he_layer() {
+    let layer = Layer::new_he(400, 400);
+    assert_eq!(layer.biases, Vector::zeros(400));
+    let expected = 2.0 / 400.0;
+    assert!((weight_variance(&layer) - expected).abs() < 0.05 * expected);
+}
+
+#[test]
+fn test_layer_input_and_output_dim() {
+    let layer: Layer = Layer::new(Matrix::zeros(3, 5), Vector::zeros(5));
+    assert_eq!(layer.input_dim(), 3);
+    assert_eq!(layer.output_dim(), 5);
+    assert_eq!(layer.forward(&Vector::ones(3)).len(), layer.output_dim());
+}
+
+#[test]
+fn test_bias_free_layer() {
+    let layer = Layer::new(Matrix::ones(2, 1), Vector::new(vec![5.0])).without_bias();
+    assert!(!layer.use_bias);
+    assert_eq!(layer.biases, Vector::zeros(1));
+    assert_eq!(layer.num_parameters(), 2);
+    assert_eq!(layer.forward(&Vector::new(vec![1.0, 2.0])), Vector::new(vec![3.0]));
+
+    let mut network = Network::new(vec![layer.clone()]);
+    network.train_epochs(&[Vector::new(vec![1.0, 2.0])], &[Vector::new(vec![1.0])], 0.1, 5).unwrap();
+    assert_eq!(network.layers[0].biases, Vector::zeros(1));
+    assert_ne!(network.layers[0].weights, layer.weights);
+}
+
+
+#[test]
+fn test_layer_backward_matches_numerical_gradient() {
+    let layer = Layer::new_seeded(3, 3, 7).with_activation(Activation::Tanh).with_residual().unwrap();
+    let input = Vector::new(vec![0.4, -0.7, 0.2]);
+    let upstream = Vector::new(vec![1.0, -2.0, 0.5]);
+    // `upstream` is the gradient of the loss `upstream · output`.
+    let loss = |layer: &Layer, input: &Vector| layer.forward(input).dot(&upstream).unwrap();
+    let (input_delta, weight_grad, bias_grad) = layer.backward(&input, &upstream);
+
+    let epsilon = 1e-3;
+    let central = |plus: f32, minus: f32| (plus - minus) / (2.0 * epsilon);
+    for i in 0..3 {
+        let (mut plus, mut minus) = (input.clone(), input.clone());
+        plus.elements[i] += epsilon;
+        minus.elements[i] -= epsilon;
+        assert!((input_delta.elements[i] - central(loss(&layer, &plus), loss(&layer, &minus))).abs() < 1e-3);
+
+        let (mut plus, mut minus) = (layer.clone(), layer.clone());
+        plus.biases.elements[i] += epsilon;
+        minus.biases.elements[i] -= epsilon;
+        assert!((bias_grad.elements[i] - central(loss(&plus, &input), loss(&minus, &input))).abs() < 1e-3);
+
+        for j in 0..3 {
+            let (mut plus, mut minus) = (layer.clone(), layer.clone());
+            plus.weights.rows[i].elements[j] += epsilon;
+            minus.weights.rows[i].elements[j] -= epsilon;
+            let numerical = central(loss(&plus, &input), loss(&minus, &input));
+            assert!((weight_grad.rows[i].elements[j] - numerical).abs() < 1e-3, "weight ({}, {}): {} vs {}", i, j, weight_grad.rows[i].elements[j], numerical);
+        }
+    }
+}
+
+#[test]
+fn test_layer_spline_curve_samples_edge() {
+    let layer = Layer::kan(2, 3, 5, 3);
+    let curve = layer.spline_curve(1, 2, 11);
+    assert_eq!(curve.len(), 11);
+    assert_eq!((curve[0].0, curve[10].0), layer.splines[1][2].domain());
+    assert!(curve.windows(2).all(|w| w[0].0 < w[1].0));
+    for &(x, y) in &curve {
+        assert_eq!(y, layer.splines[1][2].evaluate(x));
+    }
+    assert_eq!(layer.spline_curve(0, 0, 1), vec![(-1.0, layer.splines[0][0].evaluate(-1.0))]);
+    assert!(layer.spline_curve(0, 0, 0).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "No edge spline from input 0 to output 0 in a layer with 0 spline rows")]
+fn test_layer_spline_curve_requires_spline() {
+    Layer::random(2, 2).spline_curve(0, 0, 10);
+}
@@ -61,3 +61,197 @@ fn test_matrix_operations() {
         Vector::new(vec![0.4, 0.5])
     ]));
 }
+
+#[test]
+fn test_matrix_multiply_non_square() {
+    let a = Matrix::from_vec(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+    let b = Matrix::from_vec(vec![vec![7.0, 8.0], vec![9.0, 10.0], vec![11.0, 12.0]]);
+
+    let result = a.multiply(&b).unwrap();
+    assert_eq!(result, Matrix::from_vec(vec![vec![58.0, 64.0], vec![139.0, 154.0]]));
+}
+
+#[test]
+fn test_matrix_multiply_identity() {
+    let a = Matrix::from_vec(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+    let identity = Matrix::from_vec(vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]]);
+
+    assert_eq!(a.multiply(&identity).unwrap(), a);
+}
+
+#[test]
+fn test_matrix_multiply_dimension_mismatch() {
+    let a: Matrix = Matrix::ones(2, 3);
+    let b = Matrix::ones(2, 3);
+
+    let error = a.multiply(&b).unwrap_err();
+    assert_eq!(error, "Matrix dimensions do not match for multiplication: 2x3 and 2x3");
+}
+
+#[test]
+fn test_matrix_transpose() {
+    let m = Matrix::from_vec(vec![vec![1.0, 2.0, 3.0, 4.0], vec![5.0, 6.0, 7.0, 8.0]]);
+
+    let transposed = m.transpose();
+    assert_eq!(transposed.shape(), (4, 2));
+    assert_eq!(transposed, Matrix::from_vec(vec![
+        vec![1.0, 5.0],
+        vec![2.0, 6.0],
+        vec![3.0, 7.0],
+        vec![4.0, 8.0],
+    ]));
+    assert_eq!(transposed.get_element(3, 1), m.get_element(1, 3));
+    assert_eq!(m.transpose().transpose(), m);
+}
+
+#[test]
+fn test_matrix_identity() {
+    let identity: Matrix = Matrix::identity(3);
+    assert!(identity.is_square());
+    assert_eq!(identity.sum(), 3.0);
+
+    let m = Matrix::from_vec(vec![vec![2.0, -1.0, 0.5], vec![3.0, 4.0, 5.0], vec![-6.0, 7.0, 8.0]]);
+    assert_eq!(identity.multiply(&m).unwrap(), m);
+    assert_eq!(m.multiply(&identity).unwrap(), m);
+
+    assert!(!Matrix::<f32>::zeros(2, 3).is_square());
+}
+
+#[test]
+fn test_matrix_from_rows() {
+    let m = Matrix::from_rows(vec![
+        Vector::new(vec![1.0, 2.0, 3.0]),
+        Vector::new(vec![4.0, 5.0, 6.0]),
+    ]).unwrap();
+    assert_eq!(m.shape(), (2, 3));
+    assert_eq!(m.row(1), Vector::new(vec![4.0, 5.0, 6.0]));
+    assert_eq!(m.col(1), Vector::new(vec![2.0, 5.0]));
+
+    let error = Matrix::from_rows(vec![Vector::new(vec![1.0, 2.0]), Vector::new(vec![3.0])]).unwrap_err();
+    assert_eq!(error, "Row 1 has length 1 but the first row has length 2");
+}
+
+#[test]
+fn test_matrix_display() {
+    let m = Matrix::from_vec(vec![vec![1.0, -2.5], vec![10.0, 3.0]]);
+    assert_eq!(format!("{}", m), "[   1, -2.5]\n[  10,    3]");
+    assert_eq!(format!("{:.1}", m), "[ 1.0, -2.5]\n[10.0,  3.0]");
+    assert_eq!(format!("{}", Vector::new(vec![1.0, 0.5])), "[1, 0.5]");
+    // The serialization format is unaffected.
+    #[cfg(feature = "std")]
+    assert_eq!(Matrix::from_string(&m.to_string()).unwrap(), m);
+}
+
+#[test]
+fn test_matrix_in_place_ops_match_allocating_ops() {
+    let a = Matrix::from_vec(vec![vec![1.0, -2.0], vec![3.5, 4.0]]);
+    let b = Matrix::from_vec(vec![vec![0.5, 1.0], vec![-1.0, 2.0]]);
+
+    let mut m = a.clone();
+    m.scalar_multiply_mut(3.0);
+    assert_eq!(m, a.scalar_multiply(3.0));
+
+    let mut m = a.clone();
+    m.add_mut(&b).unwrap();
+    assert_eq!(m, a.add(&b).unwrap());
+
+    let mut m = a.clone();
+    m.sub_mut(&b).unwrap();
+    assert_eq!(m, a.subtract(&b).unwrap());
+    assert_eq!(a.clone() - b.clone(), m);
+
+    let error = m.add_mut(&Matrix::zeros(3, 2)).unwrap_err();
+    assert_eq!(error, "Matrix dimensions do not match for addition: 2x2 and 3x2");
+}
+
+#[test]
+fn test_matrix_apply_and_vector_map() {
+    let m = Matrix::from_vec(vec![vec![1.0, -2.0], vec![-0.5, 3.0]]);
+    assert_eq!(m.apply(|x| x * 2.0), Matrix::from_vec(vec![vec![2.0, -4.0], vec![-1.0, 6.0]]));
+    assert_eq!(m.apply(f32::abs), Matrix::from_vec(vec![vec![1.0, 2.0], vec![0.5, 3.0]]));
+
+    let v = Vector::new(vec![-1.5, 2.0]);
+    assert_eq!(v.map(|x| x * 2.0), Vector::new(vec![-3.0, 4.0]));
+    assert_eq!(v.map(f32::abs), Vector::new(vec![1.5, 2.0]));
+}
+
+#[test]
+fn test_matrix_determinant_and_inverse() {
+    let m: Matrix = Matrix::from_vec(vec![vec![4.0, 7.0], vec![2.0, 6.0]]);
+    assert!((m.determinant().unwrap() - 10.0).abs() < 1e-5);
+    let inverse = m.inverse().unwrap();
+    let expected = Matrix::from_vec(vec![vec![0.6, -0.7], vec![-0.2, 0.4]]);
+    for (row, expected_row) in inverse.rows.iter().zip(&expected.rows) {
+        assert!(row.elements.iter().zip(&expected_row.elements).all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+
+    let identity: Matrix = Matrix::identity(3);
+    assert_eq!(identity.determinant().unwrap(), 1.0);
+    assert_eq!(identity.inverse().unwrap(), identity);
+
+    let singular = Matrix::from_vec(vec![vec![1.0, 2.0], vec![2.0, 4.0]]);
+    assert_eq!(singular.determinant().unwrap(), 0.0);
+    assert_eq!(singular.inverse().unwrap_err(), "Matrix is singular and cannot be inverted");
+    assert!(Matrix::<f32>::zeros(2, 3).determinant().is_err());
+    assert!(Matrix::<f32>::zeros(2, 3).inverse().is_err());
+}
+
+#[test]
+fn test_matrix_inverse_of_small_scaled_identity() {
+    let small: Matrix = Matrix::identity(3).scalar_multiply(1e-8);
+    assert!(small.determinant().unwrap() > 0.0);
+    let inverse = small.inverse().unwrap();
+    assert!(inverse.approx_eq(&Matrix::identity(3).scalar_multiply(1e8), 1.0));
+
+    let singular: Matrix = Matrix::from_vec(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).scalar_multiply(1e-8);
+    assert!(singular.inverse().is_err());
+    assert!(Matrix::<f32>::zeros(2, 2).inverse().is_err());
+}
+
+#[test]
+fn test_matrix_hadamard() {
+    let a = Matrix::from_vec(vec![vec![1.0, -2.0], vec![3.0, 0.5]]);
+    let b = Matrix::from_vec(vec![vec![4.0, 0.5], vec![-1.0, 2.0]]);
+    assert_eq!(a.hadamard(&b).unwrap(), Matrix::from_vec(vec![vec![4.0, -1.0], vec![-3.0, 1.0]]));
+    assert_eq!(a.hadamard(&b).unwrap(), a.elementwise_multiply(&b).unwrap());
+
+    let error = a.hadamard(&Matrix::ones(2, 3)).unwrap_err();
+    assert_eq!(error, "Matrix dimensions do not match for Hadamard product: 2x2 and 2x3");
+}
+
+#[test]
+fn test_matrix_approx_eq() {
+    let m = Matrix::new(vec![Vector::new(vec![1.0, 2.0]), Vector::new(vec![3.0, 4.0])]);
+    let near = Matrix::new(vec![Vector::new(vec![1.0, 2.0 + 1e-7]), Vector::new(vec![3.0 - 1e-7, 4.0])]);
+    assert!(m.approx_eq(&near, 1e-6));
+    assert!(!m.approx_eq(&m.scalar_multiply(2.0), 1e-6));
+    assert!(!m.approx_eq(&Matrix::zeros(2, 3), 1e-6));
+    assert!(!m.approx_eq(&Matrix::new(vec![Vector::new(vec![1.0, 2.0])]), 1e-6));
+}
+
+#[test]
+fn test_matrix_clamp() {
+    let m = Matrix::from_vec(vec![vec![-3.0, 0.2], vec![0.9, 7.0]]);
+    assert_eq!(m.clamp(0.0, 1.0), Matrix::from_vec(vec![vec![0.0, 0.2], vec![0.9, 1.0]]));
+    assert_eq!(m.clamp(-1.0, -1.0), Matrix::from_vec(vec![vec![-1.0; 2]; 2]));
+}
+
+#[test]
+fn test_matrix_max_abs_and_spectral_norm() {
+    let diagonal: Matrix = Matrix::from_vec(vec![vec![2.0, 0.0, 0.0], vec![0.0, -5.0, 0.0], vec![0.0, 0.0, 1.0]]);
+    assert_eq!(diagonal.max_abs(), 5.0);
+    assert!((diagonal.spectral_norm_estimate(50) - 5.0).abs() < 1e-4);
+    assert!(diagonal.spectral_norm_estimate(0) <= 5.0);
+
+    // The norm of [[1, 1], [0, 1]] is the golden ratio, above its max entry.
+    let shear: Matrix = Matrix::from_vec(vec![vec![1.0, 1.0], vec![0.0, 1.0]]);
+    assert!((shear.spectral_norm_estimate(50) - 1.618_034).abs() < 1e-4);
+    assert_eq!(Matrix::<f32>::zeros(2, 3).spectral_norm_estimate(10), 0.0);
+
+    // The all-ones vector is in the null space of [[1, -1]], whose norm is √2.
+    let difference: Matrix = Matrix::from_vec(vec![vec![1.0, -1.0]]);
+    assert!((difference.spectral_norm_estimate(20) - 2.0f32.sqrt()).abs() < 1e-4);
+    // The ramp (1, 2) is in the null space of [[2, -1]], forcing a restart.
+    let ramp_null: Matrix = Matrix::from_vec(vec![vec![2.0, -1.0]]);
+    assert!((ramp_null.spectral_norm_estimate(20) - 5.0f32.sqrt()).abs() < 1e-4);
+}
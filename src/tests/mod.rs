@@ -1,4 +1,19 @@
 mod vector_tests;
 mod matrix_tests;
+#[cfg(feature = "std")]
 mod layer_tests;
+#[cfg(feature = "std")]
 mod network_tests;
+#[cfg(feature = "std")]
+mod spline_tests;
+#[cfg(feature = "std")]
+mod optimizer_tests;
+#[cfg(feature = "std")]
+mod schedule_tests;
+#[cfg(feature = "std")]
+mod activation_tests;
+#[cfg(feature = "std")]
+mod data_tests;
+#[cfg(feature = "std")]
+mod onnx_tests;
+mod no_std_tests;
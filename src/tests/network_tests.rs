@@ -1,5 +1,7 @@
-use crate::network::Network;
+use crate::network::{clip_gradients, ensemble_predict, weighted_ensemble_predict, InitScheme, LrSchedule, Network, NetworkError, NetworkFile};
 use crate::data_structures::{Matrix, Vector, Layer};
+use crate::utils::activations::Activation;
+use crate::utils::loss_functions::{CrossEntropyLoss, HuberLoss, Loss, MaeLoss, MseLoss};
 
 
 #[test]
@@ -21,8 +23,6 @@ fn test_network_forward() {
     let network = Network::new(vec![layer1, layer2]);
     let input = Vector::new(vec![1.0, 2.0]);
     let output = network.forward(input);
-    // Test that it runs without panicking
-    assert!(true);
 
     // Test that the output has the correct shape
     assert_eq!(output.len(), 2);
@@ -49,10 +49,1208 @@ fn test_network_backward() {
     let output = network.forward(input);
     let target = Vector::new(vec![1.0, 2.0]);
     let error = network.backward(output, target);
-    
-    // Test that it runs without panicking
-    assert!(true);
-    
+
     // Test that the error has the correct shape
     assert_eq!(error.1.len(), 2);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_network_update_all_layers() {
+    let mut network = Network::new(vec![
+        Layer::random(3, 4),
+        Layer::random(4, 3),
+        Layer::random(3, 2),
+    ]);
+    let input = Vector::new(vec![0.5, -1.0, 2.0]);
+    let target = Vector::new(vec![10.0, -10.0]);
+    let before = network.weights();
+
+    let (weight_gradients, deltas) = network.backward(input, target);
+    assert_eq!(weight_gradients.len(), 3);
+    assert_eq!(deltas.len(), 3);
+    for (gradient, layer) in weight_gradients.iter().zip(&network.layers) {
+        assert_eq!(gradient.shape(), layer.weights.shape());
+    }
+
+    network.update(&weight_gradients, &deltas, 0.1);
+    let after = network.weights();
+    assert_ne!(before[1], after[1]);
+    assert_ne!(before[2], after[2]);
+}
+
+#[test]
+fn test_network_backward_hidden_orientation() {
+    let hidden = Layer::new(
+        Matrix::from_vec(vec![vec![1.0, 0.0, 1.0], vec![0.0, 1.0, 1.0]]),
+        Vector::zeros(3),
+    );
+    let output = Layer::new(Matrix::ones(3, 1), Vector::zeros(1));
+    let network = Network::new(vec![hidden, output]);
+
+    let (weight_gradients, deltas) = network.backward(Vector::new(vec![1.0, 2.0]), Vector::new(vec![4.0]));
+
+    assert_eq!(deltas[1], Vector::new(vec![2.0]));
+    assert_eq!(deltas[0], Vector::new(vec![2.0, 2.0, 2.0]));
+    assert_eq!(weight_gradients[1], Matrix::from_vec(vec![vec![2.0], vec![4.0], vec![6.0]]));
+    assert_eq!(weight_gradients[0], Matrix::from_vec(vec![vec![2.0, 2.0, 2.0], vec![4.0, 4.0, 4.0]]));
+}
+
+#[test]
+fn test_network_load_missing_file() {
+    let result = Network::load("this/path/does/not/exist.kan");
+    assert!(matches!(result, Err(NetworkError::Io(_))));
+}
+
+#[test]
+fn test_network_json_round_trip() -> Result<(), serde_json::Error> {
+    let network = Network::new(vec![Layer::random(3, 4), Layer::kan(4, 2, 5, 3)]);
+    assert_eq!(Network::from_json(&network.to_json()?)?, network);
+    Ok(())
+}
+
+#[test]
+fn test_network_train_learning_rate() {
+    let network = Network::new(vec![Layer::random(2, 3), Layer::random(3, 1)]);
+    let inputs = vec![Vector::new(vec![1.0, -1.0]), Vector::new(vec![0.5, 2.0])];
+    let targets = vec![Vector::new(vec![3.0]), Vector::new(vec![-2.0])];
+
+    let weight_change = |learning_rate: f32| {
+        let mut trained = network.clone();
+        trained.train_epoch(&inputs, &targets, learning_rate).unwrap();
+        let weights: f32 = trained.weights().iter().zip(network.weights()).map(|(a, b)| a.subtract(&b).unwrap().map(f32::abs).sum()).sum();
+        let biases: f32 = trained.biases().iter().zip(network.biases()).map(|(a, b)| a.subtract(&b).map(f32::abs).sum()).sum();
+        (weights, biases)
+    };
+
+    let (small_weights, small_biases) = weight_change(1e-4);
+    let (large_weights, large_biases) = weight_change(1e-2);
+    assert!(large_weights > small_weights);
+    assert!(large_biases > small_biases);
+}
+
+#[test]
+fn test_network_train_minibatch_ragged() {
+    let mut network = Network::new(vec![Layer::random(2, 1)]);
+    let inputs: Vec<Vector> = (0..5).map(|i| Vector::new(vec![i as f32 * 0.2, 1.0 - i as f32 * 0.3])).collect();
+    let targets: Vec<Vector> = inputs.iter().map(|x| Vector::new(vec![x.get_element(0) + 2.0 * x.get_element(1)])).collect();
+
+    let initial_loss = network.loss_batch(&inputs, &targets);
+    network.train_minibatches(&inputs, &targets, 0.1, 2, 100);
+    assert!(network.loss_batch(&inputs, &targets) < initial_loss);
+}
+
+#[test]
+fn test_network_mse_loss() {
+    let layer = Layer::new(Matrix::from_vec(vec![vec![1.0, 0.0, 2.0], vec![0.0, 1.0, 0.0]]), Vector::zeros(3));
+    let network = Network::new(vec![layer]);
+
+    // Output is [1, 2, 2]; the errors are [1, -1, 2].
+    let loss = network.mse_loss(Vector::new(vec![1.0, 2.0]), Vector::new(vec![0.0, 3.0, 0.0]));
+    assert!((loss - 2.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_network_weight_decay() {
+    let mut network = Network::new(vec![Layer::random(3, 2)]).with_weight_decay(0.5);
+    // A zero input whose target is the current output yields zero gradients,
+    // so only the decay term moves the weights.
+    let input = Vector::zeros(3);
+    let target = network.forward(input.clone());
+    let norm = |network: &Network| network.weights().iter().map(|w| w.map(|x| x * x).sum()).sum::<f32>().sqrt();
+
+    let mut previous = norm(&network);
+    for _ in 0..5 {
+        network.train(input.clone(), target.clone(), 0.1).unwrap();
+        let current = norm(&network);
+        assert!(current < previous);
+        previous = current;
+    }
+    assert_eq!(network.forward(input), target);
+}
+
+#[test]
+fn test_network_num_parameters_and_shapes() {
+    let network = Network::new(vec![Layer::random(4, 8), Layer::random(8, 3), Layer::random(3, 1)]);
+    assert_eq!(network.layer_shapes(), vec![(4, 8), (8, 3), (3, 1)]);
+    assert_eq!(network.num_parameters(), (4 * 8 + 8) + (8 * 3 + 3) + (3 + 1));
+
+    // Each of the 2 * 3 edges adds grid_size + degree control points.
+    let kan = Network::new(vec![Layer::kan(2, 3, 5, 3)]);
+    assert_eq!(kan.num_parameters(), 2 * 3 + 3 + 6 * 8);
+}
+
+#[test]
+fn test_network_forward_batch() {
+    let network = Network::new(vec![Layer::random(3, 4), Layer::kan(4, 2, 5, 3)]);
+    let inputs = vec![
+        Vector::new(vec![0.1, 0.2, 0.3]),
+        Vector::new(vec![-0.5, 0.0, 0.5]),
+        Vector::new(vec![1.0, -1.0, 0.25]),
+    ];
+
+    let outputs = network.forward_batch(&Matrix::new(inputs.clone()));
+    assert_eq!(outputs.shape(), (3, 2));
+    for (output, input) in outputs.rows.iter().zip(inputs) {
+        let expected = network.forward(input);
+        for (a, b) in output.elements.iter().zip(&expected.elements) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn test_network_cross_entropy_and_predict_class() {
+    let network = Network::new(vec![Layer::new(Matrix::identity(3), Vector::zeros(3))]);
+    let input = Vector::new(vec![1.0, 2.0, 3.0]);
+
+    let loss = network.cross_entropy_loss(input.clone(), Vector::new(vec![0.0, 0.0, 1.0]));
+    assert!((loss - 0.407_606).abs() < 1e-5);
+    let loss = network.cross_entropy_loss(input.clone(), Vector::new(vec![1.0, 0.0, 0.0]));
+    assert!((loss - 2.407_606).abs() < 1e-5);
+
+    assert_eq!(network.predict_class(input), 2);
+    assert_eq!(network.predict_class(Vector::new(vec![5.0, -1.0, 0.0])), 0);
+}
+
+#[test]
+fn test_network_train_with_early_stopping() {
+    let inputs = vec![Vector::new(vec![1.0, 0.0]), Vector::new(vec![0.0, 1.0])];
+    let targets = vec![Vector::new(vec![1.0]), Vector::new(vec![-1.0])];
+    // The validation targets contradict the training targets, so the
+    // validation loss only gets worse as training progresses.
+    let val_targets: Vec<Vector> = targets.iter().map(|t| t.scalar_multiply(-1.0)).collect();
+
+    let original = Network::new(vec![Layer::new(Matrix::zeros(2, 1), Vector::zeros(1))]);
+    let mut network = original.clone();
+    let epochs = network.train_with_early_stopping((&inputs, &targets), (&inputs, &val_targets), 0.1, 100, 3).unwrap();
+
+    assert_eq!(epochs, 3);
+    assert_eq!(network, original);
+
+    // With a zero learning rate the validation loss plateaus immediately.
+    let epochs = network.train_with_early_stopping((&inputs, &targets), (&inputs, &targets), 0.0, 100, 5).unwrap();
+    assert_eq!(epochs, 5);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_network_parallel_batch_matches_sequential() {
+    let network = Network::new(vec![Layer::random(4, 6), Layer::kan(6, 2, 5, 3)]);
+    let inputs: Vec<Vector> = (0..1000).map(|_| Vector::random(4)).collect();
+    let targets: Vec<Vector> = (0..1000).map(|_| Vector::random(2)).collect();
+
+    let sequential: Vec<Vector> = inputs.iter().map(|input| network.predict(input.clone())).collect();
+    assert_eq!(network.predict_batch(&inputs), sequential);
+
+    let sequential_loss: f32 = inputs.iter().zip(&targets).map(|(x, t)| network.loss(x.clone(), t.clone())).sum::<f32>() / 1000.0;
+    let sequential_accuracy: f32 = inputs.iter().zip(&targets).map(|(x, t)| network.accuracy(x.clone(), t.clone())).sum::<f32>() / 1000.0;
+    assert_eq!(network.loss_batch(&inputs, &targets), sequential_loss);
+    assert_eq!(network.accuracy_batch(&inputs, &targets), sequential_accuracy);
+}
+
+#[test]
+fn test_clip_gradients() {
+    let mut weight_grad: Matrix = Matrix::from_vec(vec![vec![300.0, 0.0], vec![0.0, 400.0]]);
+    let mut bias_grad: Vector = Vector::new(vec![0.0, 1200.0]);
+    clip_gradients(&mut weight_grad, &mut bias_grad, 5.0);
+    let norm = (weight_grad.map(|g| g * g).sum() + bias_grad.dot(&bias_grad).unwrap()).sqrt();
+    assert!((norm - 5.0).abs() < 1e-4);
+    // The direction is preserved.
+    assert!((weight_grad.get_element(1, 1).unwrap() / bias_grad.get_element(1) - 1.0 / 3.0).abs() < 1e-6);
+
+    let mut small_weight_grad = Matrix::ones(2, 2).scalar_multiply(0.1);
+    let mut small_bias_grad = Vector::ones(2).scalar_multiply(0.1);
+    clip_gradients(&mut small_weight_grad, &mut small_bias_grad, 5.0);
+    assert_eq!(small_weight_grad, Matrix::ones(2, 2).scalar_multiply(0.1));
+    assert_eq!(small_bias_grad, Vector::ones(2).scalar_multiply(0.1));
+}
+
+#[test]
+fn test_network_train_reports_non_finite_parameters() {
+    let mut network = Network::new(vec![Layer::new(Matrix::new(vec![Vector::new(vec![1.0])]), Vector::zeros(1))]);
+    assert!(!network.has_nan());
+
+    let inputs = vec![Vector::new(vec![1e10])];
+    let targets = vec![Vector::new(vec![0.0])];
+    let error = network.train_epochs(&inputs, &targets, 1e30, 10).unwrap_err();
+    assert!(matches!(error, NetworkError::NonFinite { epoch: Some(_), layer: 0 }));
+    assert!(error.to_string().starts_with("Non-finite parameters in layer 0 during epoch"));
+    assert!(network.has_nan());
+}
+
+#[test]
+fn test_network_train_reports_overflowing_forward_pass() {
+    // The first step leaves huge but finite parameters, which overflow the
+    // output on the next epoch.
+    let mut network = Network::new(vec![Layer::new(Matrix::new(vec![Vector::new(vec![1.0])]), Vector::zeros(1))]);
+    let error = network.train_epochs(&[Vector::new(vec![1e19])], &[Vector::new(vec![0.0])], 1.0, 3).unwrap_err();
+    assert!(matches!(error, NetworkError::NonFinite { epoch: Some(1), layer: 0 }));
+    assert!(!network.has_nan());
+}
+
+#[test]
+fn test_network_train_with_gradient_clipping() {
+    let original: Network = Network::new(vec![Layer::new(Matrix::zeros(2, 1), Vector::zeros(1))]);
+    let mut network = original.clone().with_gradient_clipping(1.0);
+    network.train(Vector::new(vec![100.0, 100.0]), Vector::new(vec![1e6]), 0.1).unwrap();
+
+    let step = network.layers[0].weights.subtract(&original.layers[0].weights).unwrap().map(|g| g * g).sum()
+        + network.layers[0].biases.subtract(&original.layers[0].biases).magnitude().powi(2);
+    assert!((step.sqrt() - 0.1).abs() < 1e-5);
+}
+
+#[test]
+fn test_network_display() {
+    let network = Network::new(vec![Layer::random(2, 3), Layer::kan(3, 1, 5, 3)]);
+    assert_eq!(
+        format!("{}", network),
+        "Network (2 layers, 37 parameters)\n  0: Layer(2 -> 3)\n  1: KAN Layer(3 -> 1, 8 control points, degree 3)"
+    );
+}
+
+#[test]
+fn test_network_string_round_trip() {
+    let network = Network::new(vec![Layer::random(3, 4), Layer::random(4, 4), Layer::random(4, 2)]);
+    assert_eq!(Network::from_str(&network.to_string()), network);
+}
+
+#[test]
+fn test_network_save_load_round_trip() {
+    let network = Network::new(vec![Layer::random(2, 3), Layer::random(3, 1)]);
+    let path = std::env::temp_dir().join(format!("kan_round_trip_{}.txt", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    network.save(path).unwrap();
+    let loaded = Network::load(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded, network);
+}
+
+#[test]
+fn test_network_save_load_keeps_layer_fields() {
+    let mut kan = Layer::kan(2, 3, 5, 3).with_activation(Activation::Relu).without_bias();
+    kan.dropout = 0.25;
+    let network = Network::new(vec![kan, Layer::random(3, 1).with_activation(Activation::Tanh).with_batch_norm()]);
+    let path = std::env::temp_dir().join(format!("kan_layer_fields_{}.txt", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    network.save(path).unwrap();
+    let loaded = Network::load(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded, network);
+    let input = Vector::new(vec![0.3, -0.8]);
+    assert_eq!(loaded.predict(input.clone()), network.predict(input));
+
+    // Files in the text format are still read.
+    let plain = Network::new(vec![Layer::random(2, 3)]);
+    std::fs::write(path, plain.to_string()).unwrap();
+    let loaded = Network::load(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded, plain);
+}
+
+#[test]
+fn test_network_binary_round_trip() {
+    let network = Network::new(vec![Layer::new_xavier(16, 32), Layer::new_xavier(32, 4)]).with_weight_decay(0.01);
+    let dir = std::env::temp_dir();
+    let binary_path = dir.join(format!("kan_binary_{}.bin", std::process::id()));
+    let text_path = dir.join(format!("kan_binary_{}.txt", std::process::id()));
+    let (binary_path, text_path) = (binary_path.to_str().unwrap(), text_path.to_str().unwrap());
+
+    network.save_binary(binary_path).unwrap();
+    network.save(text_path).unwrap();
+    let loaded = Network::load_binary(binary_path).unwrap();
+    let (binary_size, text_size) = (std::fs::metadata(binary_path).unwrap().len(), std::fs::metadata(text_path).unwrap().len());
+    std::fs::remove_file(binary_path).unwrap();
+    std::fs::remove_file(text_path).unwrap();
+
+    assert_eq!(loaded, network);
+    assert!(binary_size < text_size, "binary {} bytes, text {} bytes", binary_size, text_size);
+
+    // Unlike the text form, the binary form keeps the edge splines.
+    let network = Network::new(vec![Layer::kan(2, 3, 5, 3)]);
+    network.save_binary(binary_path).unwrap();
+    let loaded = Network::load_binary(binary_path).unwrap();
+    std::fs::remove_file(binary_path).unwrap();
+    assert_eq!(loaded, network);
+}
+
+#[test]
+fn test_network_file_round_trip_with_metadata() {
+    let path = std::env::temp_dir().join(format!("kan_file_{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    let file = NetworkFile::new(Network::new_seeded(&[(3, 5), (5, 2)], 4));
+    assert_eq!(file.version, NetworkFile::VERSION);
+    assert_eq!(file.arch, vec![(3, 5), (5, 2)]);
+    assert!(file.created.parse::<u64>().unwrap() > 0);
+
+    file.save(path).unwrap();
+    let loaded = NetworkFile::load(path).unwrap();
+    assert_eq!(Network::load_binary(path).unwrap(), file.network);
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded, file);
+}
+
+#[test]
+fn test_network_file_rejects_newer_version() {
+    let path = std::env::temp_dir().join(format!("kan_file_version_{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mut file = NetworkFile::new(Network::new_seeded(&[(2, 2)], 1));
+    file.version = NetworkFile::VERSION + 1;
+    file.save(path).unwrap();
+
+    let error = Network::load_binary(path).unwrap_err();
+    std::fs::remove_file(path).unwrap();
+    assert!(matches!(error, NetworkError::VersionMismatch { found, expected } if found == NetworkFile::VERSION + 1 && expected == NetworkFile::VERSION));
+    assert_eq!(error.to_string(), format!("Unsupported model file version {}, expected version {}", NetworkFile::VERSION + 1, NetworkFile::VERSION));
+}
+
+#[test]
+fn test_network_regression_metrics() {
+    let network = Network::new(vec![Layer::new(Matrix::from_vec(vec![vec![2.0, 0.0], vec![0.0, -1.0]]), Vector::new(vec![1.0, 0.0]))]);
+    let inputs = vec![Vector::new(vec![0.0, 1.0]), Vector::new(vec![1.0, 2.0]), Vector::new(vec![2.0, -1.0])];
+    let targets = network.predict_batch(&inputs);
+    assert_eq!(network.r2_score(&inputs, &targets), 1.0);
+    assert_eq!(network.mae(&inputs, &targets), 0.0);
+
+    // A network that always predicts the target mean scores zero.
+    let mean = Network::new(vec![Layer::new(Matrix::zeros(2, 2), Vector::new(vec![3.0, -2.0 / 3.0]))]);
+    assert!(mean.r2_score(&inputs, &targets).abs() < 1e-6);
+
+    let shifted: Vec<Vector> = targets.iter().map(|t| t.add_scalar(0.5)).collect();
+    assert!((network.mae(&inputs, &shifted) - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn test_network_r2_score_with_constant_targets() {
+    let network = Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::new(vec![2.0]))]);
+    let inputs = vec![Vector::new(vec![0.0]), Vector::new(vec![1.0])];
+    assert_eq!(network.r2_score(&inputs, &[Vector::new(vec![2.0]), Vector::new(vec![2.0])]), 1.0);
+    assert_eq!(network.r2_score(&inputs, &[Vector::new(vec![1.0]), Vector::new(vec![1.0])]), 0.0);
+}
+
+#[test]
+#[should_panic(expected = "R² needs at least one sample")]
+fn test_network_r2_score_panics_without_samples() {
+    Network::new(vec![Layer::random(1, 1)]).r2_score(&[], &[]);
+}
+
+#[test]
+fn test_network_dropout_modes() {
+    let mut network = Network::new(vec![Layer::new(Matrix::ones(4, 100), Vector::zeros(100)).with_dropout(0.5)]);
+    let input = Vector::ones(4);
+
+    let eval = network.forward(input.clone());
+    assert_eq!(network.forward(input.clone()), eval);
+    assert_eq!(eval, Vector::new(vec![4.0; 100]));
+
+    network.set_training(true);
+    let first = network.forward(input.clone());
+    let second = network.forward(input.clone());
+    assert_ne!(first, second);
+    // Survivors are scaled by 1 / (1 - p).
+    assert!(first.elements.iter().all(|&x| x == 0.0 || x == 8.0));
+
+    network.set_training(false);
+    assert_eq!(network.forward(input), eval);
+}
+
+#[test]
+fn test_network_builder() {
+    let network = Network::builder().add_layer(3, 4).add_layer(4, 2).init(InitScheme::Xavier).build().unwrap();
+    assert_eq!(network.layer_shapes(), vec![(3, 4), (4, 2)]);
+    assert_eq!(network.layers[1].biases, Vector::zeros(2));
+
+    let error = Network::builder().add_layer(3, 4).add_layer(5, 2).build().unwrap_err();
+    assert_eq!(error, "Layer 0 outputs 4 values but layer 1 expects 5 inputs");
+    assert!(Network::builder().build().is_err());
+}
+
+#[test]
+fn test_network_builder_activation() {
+    let network = Network::builder().add_layer(2, 3).with_activation(Activation::Relu).add_layer(3, 1).build().unwrap();
+    assert_eq!(network.layers[0].activation, Activation::Relu);
+    assert_eq!(network.layers[1].activation, Activation::Identity);
+}
+
+#[test]
+fn test_network_l1_and_prune() {
+    let count_nonzero = |network: &Network| network.layers[0].weights.rows.iter().flat_map(|row| &row.elements).filter(|&&w| w != 0.0).count();
+    let inputs: Vec<Vector> = (0..20)
+        .map(|i| {
+            let x = i as f32 / 10.0 - 1.0;
+            Vector::new(vec![x, (3.0 * x).sin(), x * x - 0.5, (2.0 * x).cos()])
+        })
+        .collect();
+    // Only the first input carries signal.
+    let targets: Vec<Vector> = inputs.iter().map(|x| Vector::new(vec![x.elements[0]])).collect();
+
+    let mut network = Network::new(vec![Layer::new(Matrix::ones(4, 1).scalar_multiply(0.5), Vector::zeros(1))]).with_l1(0.01);
+    assert_eq!(count_nonzero(&network), 4);
+    network.train_epochs(&inputs, &targets, 0.05, 200).unwrap();
+    network.prune(0.02);
+
+    assert!(count_nonzero(&network) < 4);
+    assert!(network.layers[0].weights.rows[0].elements[0] != 0.0);
+    assert!(network.loss_batch(&inputs, &targets) < 0.05);
+}
+
+#[test]
+fn test_network_gradient_check() {
+    let network = Network::new(vec![
+        Layer::new(
+            Matrix::from_vec(vec![vec![0.3, -0.2, 0.5], vec![0.1, 0.4, -0.6]]),
+            Vector::new(vec![0.1, -0.1, 0.05]),
+        )
+        .with_activation(Activation::Tanh),
+        Layer::kan(3, 2, 5, 3),
+    ]);
+    let error = network.gradient_check(&Vector::new(vec![0.5, -0.8]), &Vector::new(vec![1.0, -1.0]), 1e-2);
+    assert!(error < 1e-3, "gradient check error {}", error);
+}
+
+#[test]
+fn test_network_train_epoch_uses_every_sample() {
+    let inputs = vec![
+        Vector::new(vec![0.0, 0.0]),
+        Vector::new(vec![0.0, 1.0]),
+        Vector::new(vec![1.0, 0.0]),
+        Vector::new(vec![1.0, 1.0]),
+    ];
+    let targets = vec![Vector::new(vec![0.0]), Vector::new(vec![1.0]), Vector::new(vec![1.0]), Vector::new(vec![0.0])];
+    let initial = Network::new(vec![Layer::random(2, 3).with_activation(Activation::Tanh), Layer::random(3, 1)]);
+
+    let mut trained = initial.clone();
+    trained.train_epoch(&inputs, &targets, 0.1).unwrap();
+
+    let mut expected = initial.clone();
+    for (input, target) in inputs.iter().zip(&targets) {
+        expected.train(input.clone(), target.clone(), 0.1).unwrap();
+    }
+    assert_eq!(trained, expected);
+
+    // Repeating the first sample four times is not the same.
+    let mut repeated = initial.clone();
+    for _ in 0..4 {
+        repeated.train(inputs[0].clone(), targets[0].clone(), 0.1).unwrap();
+    }
+    assert_ne!(trained, repeated);
+}
+
+#[test]
+fn test_network_confusion_matrix() {
+    // The identity network predicts the class of the largest input.
+    let network = Network::new(vec![Layer::new(Matrix::identity(3), Vector::zeros(3))]);
+    let one_hot = |class: usize| {
+        let mut v = Vector::zeros(3);
+        v.elements[class] = 1.0;
+        v
+    };
+    let inputs = vec![one_hot(0), one_hot(0), one_hot(1), one_hot(2), one_hot(2)];
+    let targets = vec![one_hot(0), one_hot(1), one_hot(1), one_hot(2), one_hot(0)];
+
+    let confusion = network.confusion_matrix(&inputs, &targets, 3);
+    assert_eq!(
+        confusion,
+        Matrix::from_vec(vec![vec![1.0, 0.0, 1.0], vec![1.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]])
+    );
+}
+
+#[test]
+fn test_network_forward_cached() {
+    let weights = Matrix::from_vec(vec![vec![1.0, -2.0], vec![0.5, 1.0]]);
+    let biases = Vector::new(vec![0.25, -0.5]);
+    let network = Network::new(vec![
+        Layer::new(weights.clone(), biases.clone()).with_activation(Activation::Relu),
+        Layer::random(2, 1),
+    ]);
+    let input = Vector::new(vec![2.0, 1.0]);
+
+    let cache = network.forward_cached(input.clone());
+    let z = weights.transpose().multiply_with_vector(&input).unwrap().add(&biases).unwrap();
+    assert_eq!(cache.pre_activations[0], z);
+    assert_eq!(cache.activations[1], Activation::Relu.apply(&z));
+    assert_eq!(cache.activations.len(), 3);
+    assert_eq!(cache.activations[2], network.forward(input.clone()));
+
+    // A new call starts from a fresh cache.
+    let other = network.forward_cached(Vector::new(vec![-1.0, 0.0]));
+    assert_eq!(other.pre_activations.len(), 2);
+    assert_ne!(other.pre_activations[0], cache.pre_activations[0]);
+}
+
+#[test]
+fn test_network_gradient_norm() {
+    let network = Network::new(vec![Layer::new(Matrix::identity(2), Vector::zeros(2))]);
+    let input = Vector::new(vec![1.0, 2.0]);
+    assert!(network.gradient_norm(&input, &input) < 1e-6);
+
+    // Error [1, 0] gives a bias gradient [1, 0] and weight gradient [[1, 0], [2, 0]].
+    let target = Vector::new(vec![0.0, 2.0]);
+    assert!((network.gradient_norm(&input, &target) - 6.0f32.sqrt()).abs() < 1e-6);
+}
+
+#[test]
+fn test_network_train_epochs_with_callback() {
+    let mut network = Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::zeros(1))]);
+    let inputs = vec![Vector::new(vec![1.0]), Vector::new(vec![-1.0])];
+    let targets = vec![Vector::new(vec![2.0]), Vector::new(vec![-2.0])];
+
+    let mut calls = Vec::new();
+    network.train_epochs_with_callback(&inputs, &targets, 0.1, 5, |epoch, loss, accuracy| calls.push((epoch, loss, accuracy))).unwrap();
+
+    assert_eq!(calls.iter().map(|call| call.0).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+    assert!(calls.windows(2).all(|pair| pair[1].1 < pair[0].1));
+    assert_eq!(calls[4].1, network.loss_batch(&inputs, &targets));
+}
+
+#[test]
+fn test_network_huber_loss_resists_outliers() {
+    let network = Network::new(vec![Layer::new(Matrix::ones(1, 1), Vector::zeros(1))]);
+    let input = Vector::new(vec![1.0]);
+    assert_eq!(network.huber_loss(input.clone(), Vector::new(vec![1.5]), 1.0), 0.125);
+    assert_eq!(network.huber_loss(input.clone(), Vector::new(vec![11.0]), 1.0), 9.5);
+
+    // Fit y = 2x on data with one large outlier.
+    let inputs: Vec<Vector> = (1..=5).map(|i| Vector::new(vec![i as f32 / 5.0])).collect();
+    let mut targets: Vec<Vector> = inputs.iter().map(|x| x.scalar_multiply(2.0)).collect();
+    targets[4] = Vector::new(vec![20.0]);
+
+    let initial = Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::zeros(1))]);
+    let (mut squared, mut robust) = (initial.clone(), initial);
+    for _ in 0..500 {
+        for (input, target) in inputs.iter().zip(&targets) {
+            let (weight_gradients, deltas) = squared.backward(input.clone(), target.clone());
+            squared.update(&weight_gradients, &deltas, 0.05);
+            let (weight_gradients, deltas) = robust.backward_huber(input.clone(), target.clone(), 0.5);
+            robust.update(&weight_gradients, &deltas, 0.05);
+        }
+    }
+
+    let clean_error = |network: &Network| network.predict(Vector::new(vec![0.5])).subtract(&Vector::new(vec![1.0])).magnitude();
+    assert!(clean_error(&robust) < clean_error(&squared), "huber {} vs mse {}", clean_error(&robust), clean_error(&squared));
+}
+
+#[test]
+fn test_network_seeded_initialization() {
+    assert_eq!(Layer::new_seeded(3, 2, 42), Layer::new_seeded(3, 2, 42));
+    assert_ne!(Layer::new_seeded(3, 2, 42), Layer::new_seeded(3, 2, 43));
+
+    let shapes = [(2, 4), (4, 1)];
+    let network = Network::new_seeded(&shapes, 7);
+    assert_eq!(network, Network::new_seeded(&shapes, 7));
+    assert_ne!(network, Network::new_seeded(&shapes, 8));
+    assert_eq!(network.layer_shapes(), shapes.to_vec());
+}
+
+#[test]
+fn test_network_clone_architecture() {
+    let original = Network::new(vec![Layer::random(2, 4).with_activation(Activation::Relu), Layer::kan(4, 1, 5, 3)]).with_weight_decay(0.01);
+    let clone = original.clone_architecture(3);
+    assert_eq!(clone.layer_shapes(), original.layer_shapes());
+    assert_eq!(clone.layers[0].activation, Activation::Relu);
+    assert_eq!(clone.weight_decay, 0.01);
+    assert_ne!(clone.weights(), original.weights());
+    assert_ne!(clone.layers[1].splines, original.layers[1].splines);
+    assert_eq!(clone, original.clone_architecture(3));
+}
+
+#[test]
+fn test_network_clone_architecture_keeps_bias_free_layers_zero() {
+    let original = Network::new(vec![Layer::random(2, 4).without_bias(), Layer::random(4, 1)]);
+    let clone = original.clone_architecture(5);
+    assert!(!clone.layers[0].use_bias);
+    assert_eq!(clone.layers[0].biases, Vector::zeros(4));
+    assert_ne!(clone.layers[1].biases, Vector::zeros(1));
+}
+
+#[test]
+fn test_network_accuracy_with_threshold() {
+    // The identity network predicts its input, so the errors are 0.1, 0.3 and 0.6.
+    let network = Network::new(vec![Layer::new(Matrix::identity(3), Vector::zeros(3))]);
+    let input = Vector::new(vec![0.1, 0.3, 0.6]);
+    let target = Vector::zeros(3);
+    assert!((network.accuracy_with_threshold(input.clone(), target.clone(), 0.2) - 1.0 / 3.0).abs() < 1e-6);
+    assert!((network.accuracy_with_threshold(input.clone(), target.clone(), 0.5) - 2.0 / 3.0).abs() < 1e-6);
+    assert_eq!(network.accuracy(input.clone(), target.clone()), network.accuracy_with_threshold(input, target, 0.5));
+}
+
+#[test]
+fn test_network_trains_silently_by_default() {
+    let mut network = Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::zeros(1))]);
+    assert!(!network.verbose);
+    let inputs = vec![Vector::new(vec![1.0])];
+    let targets = vec![Vector::new(vec![0.5])];
+    network.train_epochs(&inputs, &targets, 0.1, 3).unwrap();
+
+    network.set_verbose(true);
+    network.train_epochs(&inputs, &targets, 0.1, 3).unwrap();
+    assert!(network.verbose);
+}
+
+#[test]
+fn test_network_weight_histogram() {
+    let weights = Matrix::new(vec![Vector::new(vec![0.0, 1.0, 1.0]), Vector::new(vec![2.0, 3.0, 4.0])]);
+    let network = Network::new(vec![Layer::new(weights, Vector::zeros(3)), Layer::new(Matrix::zeros(3, 1), Vector::zeros(1))]);
+
+    let histogram = network.weight_histogram(0, 2);
+    assert_eq!(histogram, vec![(1.0, 3), (3.0, 3)]);
+    assert_eq!(histogram.iter().map(|&(_, count)| count).sum::<usize>(), 6);
+    assert_eq!(network.weight_histogram(1, 4), vec![(0.0, 3)]);
+
+    let empty = Network::new(vec![Layer::new(Matrix::zeros(0, 0), Vector::zeros(0))]);
+    assert!(empty.weight_histogram(0, 4).is_empty());
+}
+
+#[test]
+fn test_network_predict_proba() {
+    let network = Network::new_seeded(&[(3, 5), (5, 4)], 11);
+    let input = Vector::new(vec![0.2, -0.4, 0.9]);
+    let probabilities = network.predict_proba(input.clone());
+    assert!((probabilities.sum() - 1.0).abs() < 1e-6);
+    assert!(probabilities.elements.iter().all(|&p| p > 0.0));
+
+    assert_eq!(probabilities.argmax(), network.predict_class(input));
+}
+
+#[test]
+fn test_network_input_saliency() {
+    // A linear layer maps input i to output j with weight w_ij, so the
+    // saliency of output j is column j of the weights.
+    let weights = Matrix::new(vec![Vector::new(vec![1.0, -2.0]), Vector::new(vec![0.5, 3.0]), Vector::new(vec![-1.5, 0.0])]);
+    let network = Network::new(vec![Layer::new(weights.clone(), Vector::new(vec![0.1, 0.2]))]);
+    let input = Vector::new(vec![0.3, -0.7, 2.0]);
+    for output in 0..2 {
+        let column = Vector::new(weights.rows.iter().map(|row| row.elements[output]).collect());
+        assert_eq!(network.input_saliency(&input, output), column);
+    }
+
+    // Through a hidden layer, saliency matches a finite-difference estimate.
+    let network = Network::new(vec![Layer::new_seeded(3, 4, 5).with_activation(Activation::Sigmoid), Layer::new_seeded(4, 2, 6)]);
+    let saliency = network.input_saliency(&input, 1);
+    let epsilon = 1e-2;
+    for i in 0..3 {
+        let mut plus = input.clone();
+        plus.elements[i] += epsilon;
+        let mut minus = input.clone();
+        minus.elements[i] -= epsilon;
+        let estimate = (network.forward(plus).elements[1] - network.forward(minus).elements[1]) / (2.0 * epsilon);
+        assert!((saliency.elements[i] - estimate).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_network_partial_fit() {
+    // Stream samples of y = 2x - 1 one at a time, summing the loss of each
+    // pass over the stream.
+    let mut network = Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::zeros(1))]);
+    let samples: Vec<(Vector, Vector)> = (0..10).map(|i| {
+        let x = i as f32 / 10.0;
+        (Vector::new(vec![x]), Vector::new(vec![2.0 * x - 1.0]))
+    }).collect();
+
+    let mut pass_losses = Vec::new();
+    for _ in 0..5 {
+        let mut cumulative = 0.0;
+        for (input, target) in &samples {
+            cumulative += network.partial_fit(input.clone(), target.clone(), 0.1).unwrap();
+        }
+        pass_losses.push(cumulative);
+    }
+    assert!(pass_losses.windows(2).all(|pair| pair[1] < pair[0]));
+}
+
+#[test]
+fn test_network_train_with_checkpointing() {
+    // The learning rate jumps to a diverging 3.0 after five epochs, so the
+    // loss spikes and the network from epoch five should be restored.
+    let inputs = vec![Vector::new(vec![1.0])];
+    let targets = vec![Vector::new(vec![1.0])];
+    let initial = Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::zeros(1))]);
+    let schedule = LrSchedule::StepDecay { initial: 0.1, gamma: 30.0, step_size: 5 };
+
+    let mut network = initial.clone();
+    let best = network.train_with_checkpointing(&inputs, &targets, &schedule, 8).unwrap();
+    assert_eq!(best.epochs, 5);
+
+    let mut expected = initial;
+    expected.train_epochs(&inputs, &targets, 0.1, 5).unwrap();
+    assert_eq!(network, expected);
+    assert_eq!(best.loss, expected.evaluate_batch(&inputs, &targets).0);
+}
+
+#[test]
+fn test_network_train_layerwise() {
+    let original = Network::new_seeded(&[(2, 3), (3, 1)], 4);
+    let input = Vector::new(vec![0.5, -0.5]);
+    let target = Vector::new(vec![1.0]);
+
+    let mut network = original.clone();
+    network.train_layerwise(input.clone(), target.clone(), &[0.0, 0.1]).unwrap();
+    assert_eq!(network.layers[0], original.layers[0]);
+    assert_ne!(network.layers[1], original.layers[1]);
+
+    // Equal rates match a scalar `train` step.
+    let (mut broadcast, mut scalar) = (original.clone(), original);
+    broadcast.train_layerwise(input.clone(), target.clone(), &[0.1, 0.1]).unwrap();
+    scalar.train(input, target, 0.1).unwrap();
+    assert_eq!(broadcast, scalar);
+}
+
+#[test]
+#[should_panic(expected = "Expected one learning rate per layer, got 1 for 2 layers")]
+fn test_network_train_layerwise_rejects_wrong_rate_count() {
+    let mut network = Network::new_seeded(&[(2, 3), (3, 1)], 4);
+    network.train_layerwise(Vector::new(vec![0.5, -0.5]), Vector::new(vec![1.0]), &[0.1]).unwrap();
+}
+
+#[test]
+fn test_network_grad_wrt_layer() {
+    let network = Network::new(vec![
+        Layer::new_seeded(2, 3, 1).with_activation(Activation::Tanh),
+        Layer::new_seeded(3, 3, 2).with_activation(Activation::Sigmoid),
+        Layer::new_seeded(3, 1, 3),
+    ]);
+    let (input, target) = (Vector::new(vec![0.4, -0.9]), Vector::new(vec![0.2]));
+    let (weight_gradients, deltas) = network.backward(input.clone(), target.clone());
+    for layer in 0..3 {
+        assert_eq!(network.grad_wrt_layer(layer, &input, &target), (weight_gradients[layer].clone(), deltas[layer].clone()));
+    }
+}
+
+#[test]
+fn test_network_tie_weights() {
+    let mut network = Network::new(vec![Layer::new_seeded(2, 3, 1).with_activation(Activation::Tanh), Layer::new_seeded(3, 2, 2)]);
+    network.tie_weights(0, 1).unwrap();
+    assert_eq!(network.layers[1].weights, network.layers[0].weights.transpose());
+
+    let before = network.layers[0].weights.clone();
+    let input = Vector::new(vec![0.3, -0.6]);
+    network.train(input.clone(), input, 0.1).unwrap();
+    assert_ne!(network.layers[0].weights, before);
+    assert_eq!(network.layers[1].weights, network.layers[0].weights.transpose());
+
+    let mut untied = Network::new(vec![Layer::new_seeded(2, 3, 1), Layer::new_seeded(3, 3, 2)]);
+    assert_eq!(untied.tie_weights(0, 1).unwrap_err(), "Tied weights need transposed shapes, got 2x3 and 3x3");
+    assert!(untied.tie_weights(0, 2).is_err());
+}
+
+#[test]
+fn test_network_precision_recall_f1() {
+    // The identity network predicts its input.
+    let network = Network::new(vec![Layer::new(Matrix::identity(1), Vector::zeros(1))]);
+    let scalars = |values: &[f32]| values.iter().map(|&v| Vector::new(vec![v])).collect::<Vec<_>>();
+    // 2 true positives, 1 false positive, 2 false negatives, 1 true negative.
+    let inputs = scalars(&[0.9, 0.7, 0.6, 0.2, 0.1, 0.3]);
+    let targets = scalars(&[1.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+
+    let (precision, recall, f1) = network.precision_recall_f1(&inputs, &targets, 0.5);
+    assert!((precision - 2.0 / 3.0).abs() < 1e-6);
+    assert!((recall - 0.5).abs() < 1e-6);
+    assert!((f1 - 4.0 / 7.0).abs() < 1e-6);
+
+    // Nothing predicted positive.
+    assert_eq!(network.precision_recall_f1(&scalars(&[0.1]), &scalars(&[1.0]), 0.5), (0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_network_gradient_accumulation() {
+    let initial = Network::new(vec![Layer::random(2, 3).with_activation(Activation::Tanh), Layer::random(3, 1)]);
+    let samples = [
+        (Vector::new(vec![0.5, -1.0]), Vector::new(vec![1.0])),
+        (Vector::new(vec![-0.25, 2.0]), Vector::new(vec![0.0])),
+    ];
+
+    let mut accumulated = initial.clone();
+    for (input, target) in &samples {
+        accumulated.accumulate_gradients(input.clone(), target.clone());
+    }
+    accumulated.apply_accumulated(0.1);
+
+    let mut expected = initial.clone();
+    let (w0, d0) = initial.backward(samples[0].0.clone(), samples[0].1.clone());
+    let (w1, d1) = initial.backward(samples[1].0.clone(), samples[1].1.clone());
+    let weight_gradients: Vec<Matrix> = w0.iter().zip(&w1).map(|(a, b)| a.add(b).unwrap().scalar_multiply(0.5)).collect();
+    let deltas: Vec<Vector> = d0.iter().zip(&d1).map(|(a, b)| a.add(b).unwrap().scalar_multiply(0.5)).collect();
+    expected.update(&weight_gradients, &deltas, 0.1);
+    assert_eq!(accumulated, expected);
+
+    // The buffers are cleared, so applying again is a no-op.
+    accumulated.apply_accumulated(0.1);
+    assert_eq!(accumulated, expected);
+}
+
+#[test]
+fn test_network_freeze_layer() {
+    let mut network = Network::new(vec![Layer::random(2, 3), Layer::random(3, 1)]);
+    let initial = network.clone();
+    let (input, target) = (Vector::new(vec![0.5, -1.0]), Vector::new(vec![2.0]));
+
+    network.freeze_layer(0);
+    assert!(network.is_frozen(0) && !network.is_frozen(1));
+    network.train(input.clone(), target.clone(), 0.1).unwrap();
+    network.train_with_optimizer(input.clone(), target.clone(), &mut crate::network::Sgd::new(0.1)).unwrap();
+    assert_eq!(network.layers[0], initial.layers[0]);
+    assert_ne!(network.layers[1], initial.layers[1]);
+
+    network.unfreeze_layer(0);
+    network.train(input, target, 0.1).unwrap();
+    assert_ne!(network.layers[0], initial.layers[0]);
+}
+
+#[test]
+fn test_network_batch_norm_training_mode() {
+    let mut network = Network::new(vec![Layer::random(2, 3).with_batch_norm()]);
+    let inputs = Matrix::new(vec![
+        Vector::new(vec![1.0, 2.0]),
+        Vector::new(vec![-3.0, 0.5]),
+        Vector::new(vec![4.0, -1.0]),
+        Vector::new(vec![0.0, 5.0]),
+    ]);
+
+    network.set_training(true);
+    let outputs = network.forward_batch(&inputs);
+    let (mean, var) = crate::data_structures::BatchNorm::statistics(&outputs);
+    assert!(mean.elements.iter().all(|m| m.abs() < 1e-4));
+    assert!(var.elements.iter().all(|v| (v - 1.0).abs() < 1e-3));
+
+    // Evaluation mode uses the running estimates, which start at the identity.
+    network.set_training(false);
+    let z = network.layers[0].pre_activation_batch(&inputs);
+    let outputs = network.forward_batch(&inputs);
+    for (output, z) in outputs.rows.iter().zip(&z.rows) {
+        assert!(output.elements.iter().zip(&z.elements).all(|(o, z)| (o - z).abs() < 1e-3));
+    }
+
+    network.update_batch_norm_statistics(&inputs);
+    let (batch_mean, _) = crate::data_structures::BatchNorm::statistics(&z);
+    let batch_norm = network.layers[0].batch_norm.as_ref().unwrap();
+    assert!(batch_norm.running_mean.elements.iter().zip(&batch_mean.elements).all(|(r, m)| (r - 0.1 * m).abs() < 1e-5));
+    assert!(network.gradient_check(&Vector::new(vec![0.5, -1.0]), &Vector::new(vec![1.0, 0.0, -1.0]), 1e-2) < 1e-2);
+}
+
+#[test]
+fn test_network_forward_with_activations() {
+    let network = Network::new(vec![Layer::random(2, 3).with_activation(Activation::Tanh), Layer::random(3, 1)]);
+    let input = Vector::new(vec![0.5, -1.0]);
+
+    let activations = network.forward_with_activations(input.clone());
+    assert_eq!(activations.len(), network.layers.len() + 1);
+    assert_eq!(activations[0], input);
+    assert_eq!(activations[2], network.forward(input));
+}
+
+#[test]
+fn test_network_entropy_regularization() {
+    let inputs: Vec<Vector> = (0..20)
+        .map(|i| {
+            let x = i as f32 / 10.0 - 1.0;
+            Vector::new(vec![x, (3.0 * x).sin(), x * x - 0.5])
+        })
+        .collect();
+    let targets: Vec<Vector> = inputs.iter().map(|x| Vector::new(vec![x.elements[0] - 0.5 * x.elements[1]])).collect();
+
+    let initial = Network::new_seeded(&[(3, 2), (2, 1)], 7);
+    let uniform = Network::new(vec![Layer::new(Matrix::ones(3, 2), Vector::zeros(2))]);
+    assert!((uniform.entropy_regularization() - 6.0f32.ln()).abs() < 1e-5);
+
+    let entropy_after = |lambda_entropy: f32| {
+        let mut network = initial.clone().with_entropy_regularization(lambda_entropy);
+        network.train_epochs(&inputs, &targets, 0.05, 50).unwrap();
+        network.entropy_regularization()
+    };
+    assert!(entropy_after(0.5) < entropy_after(0.0));
+}
+
+#[test]
+fn test_network_residual_connections() {
+    // The middle layer maps everything to zero, so its Jacobian vanishes.
+    let middle = Layer::new(Matrix::zeros(3, 3), Vector::zeros(3));
+    let plain = Network::new(vec![Layer::random(2, 3), middle.clone(), Layer::random(3, 1)]);
+    let mut residual = plain.clone();
+    residual.layers[1] = middle.with_residual().unwrap();
+    let (input, target) = (Vector::new(vec![0.5, -1.0]), Vector::new(vec![2.0]));
+
+    let hidden = residual.layers[0].forward(&input);
+    assert_eq!(residual.forward_with_activations(input.clone())[2], hidden);
+
+    let (plain_gradients, _) = plain.backward(input.clone(), target.clone());
+    assert_eq!(plain_gradients[0].map(f32::abs).sum(), 0.0);
+    let (residual_gradients, _) = residual.backward(input.clone(), target.clone());
+    assert!(residual_gradients[0].map(f32::abs).sum() > 0.0);
+    assert!(residual.gradient_check(&input, &target, 1e-2) < 1e-2);
+
+    let error = Layer::random(2, 3).with_residual().unwrap_err();
+    assert_eq!(error, "A residual layer needs matching input and output dimensions, got 2 -> 3");
+}
+
+#[test]
+fn test_network_summary() {
+    let network = Network::new(vec![Layer::random(2, 3).with_activation(Activation::Relu), Layer::kan(3, 1, 5, 3)]);
+    let summary = network.summary();
+
+    let lines: Vec<&str> = summary.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert!(lines[2].contains("Dense (Relu)") && lines[2].trim_end().ends_with(&network.layers[0].num_parameters().to_string()));
+    assert!(lines[3].contains("KAN (Identity)") && lines[3].trim_end().ends_with(&network.layers[1].num_parameters().to_string()));
+    assert_eq!(lines[5], format!("Total parameters: {}", network.num_parameters()));
+}
+
+#[test]
+fn test_network_generic_scalar() {
+    let weights = [vec![vec![0.3, -0.2, 0.5], vec![0.1, 0.4, -0.6]], vec![vec![0.7], vec![-0.3], vec![0.2]]];
+    let biases = [vec![0.05, -0.1, 0.2], vec![0.1]];
+
+    let single: Network<f32> = Network::new(
+        weights
+            .iter()
+            .zip(&biases)
+            .map(|(w, b)| Layer::new(Matrix::from_vec(w.clone()), Vector::new(b.clone())).with_activation(Activation::Tanh))
+            .collect(),
+    );
+    let double: Network<f64> = Network::new(
+        weights
+            .iter()
+            .zip(&biases)
+            .map(|(w, b)| {
+                let w = w.iter().map(|row| row.iter().map(|&x| x as f64).collect()).collect();
+                Layer::new(Matrix::from_vec(w), Vector::new(b.iter().map(|&x| x as f64).collect())).with_activation(Activation::Tanh)
+            })
+            .collect(),
+    );
+
+    let output = single.forward(Vector::new(vec![0.5, -1.0]));
+    let precise = double.forward(Vector::new(vec![0.5, -1.0]));
+    assert_eq!(precise.len(), 1);
+    assert!((output.elements[0] as f64 - precise.elements[0]).abs() < 1e-6);
+    assert_eq!(double.layer_shapes(), single.layer_shapes());
+
+    // Both precisions take the same training step.
+    let (mut single, mut double) = (single.with_weight_decay(0.01), double.with_weight_decay(0.01));
+    single.train(Vector::new(vec![0.5, -1.0]), Vector::new(vec![1.0]), 0.1).unwrap();
+    double.train(Vector::new(vec![0.5, -1.0]), Vector::new(vec![1.0]), 0.1).unwrap();
+    let trained = double.forward(Vector::new(vec![0.5, -1.0]));
+    assert!((trained.elements[0] - 1.0).abs() < (precise.elements[0] - 1.0).abs());
+    let single_weights: Vec<f32> = single.weights().iter().flat_map(|w| w.to_vec()).flatten().collect();
+    let double_weights: Vec<f64> = double.weights().iter().flat_map(|w| w.to_vec()).flatten().collect();
+    for (s, d) in single_weights.iter().zip(&double_weights) {
+        assert!((*s as f64 - d).abs() < 1e-6);
+    }
+
+    let (weight_gradients, deltas) = double.backward(Vector::new(vec![0.5, -1.0]), Vector::new(vec![1.0]));
+    let before = double.clone();
+    double.update(&weight_gradients, &deltas, 0.1);
+    assert_ne!(double, before);
+}
+
+#[test]
+fn test_network_gradient_buffers() {
+    let mut network = Network::new(vec![Layer::random(2, 3).with_activation(Activation::Tanh), Layer::random(3, 1)]);
+    let (input, target) = (Vector::new(vec![0.5, -1.0]), Vector::new(vec![2.0]));
+    let (weight_gradients, deltas) = network.backward(input.clone(), target.clone());
+
+    network.zero_grad();
+    for layer in &network.layers {
+        assert_eq!(layer.weight_grad, Matrix::zeros(layer.input_dim(), layer.output_dim()));
+        assert_eq!(layer.bias_grad, Vector::zeros(layer.output_dim()));
+    }
+
+    network.backward_into_grads(input.clone(), target.clone());
+    for ((layer, weight_gradient), delta) in network.layers.iter().zip(&weight_gradients).zip(&deltas) {
+        assert_eq!(&layer.weight_grad, weight_gradient);
+        assert_eq!(&layer.bias_grad, delta);
+    }
+
+    let mut expected = network.clone();
+    expected.update(&weight_gradients, &deltas, 0.1);
+    network.step(0.1);
+    assert_eq!(network.layers[0].weights, expected.layers[0].weights);
+    assert_eq!(network.layers[1].biases, expected.layers[1].biases);
+
+    network.zero_grad();
+    assert!(network.layers.iter().all(|layer| layer.weight_grad.sum() == 0.0 && layer.bias_grad.sum() == 0.0));
+}
+
+#[test]
+fn test_network_loss_changes_gradient() {
+    let network = Network::new(vec![Layer::new(Matrix::identity(2), Vector::zeros(2))]);
+    let (input, target) = (Vector::new(vec![3.0, -0.25]), Vector::new(vec![1.0, 0.0]));
+    let output_delta = |loss: &dyn Loss| network.backward_with_loss(input.clone(), target.clone(), loss).1.remove(0);
+
+    assert_eq!(output_delta(&MseLoss), Vector::new(vec![2.0, -0.25]));
+    assert_eq!(output_delta(&MaeLoss), Vector::new(vec![1.0, -1.0]));
+    assert_eq!(output_delta(&HuberLoss { delta: 1.0 }), Vector::new(vec![1.0, -0.25]));
+    // Squared error pulls the second logit up towards 0, while cross-entropy
+    // pushes it down to favour the target class.
+    let cross_entropy = output_delta(&CrossEntropyLoss);
+    assert!(cross_entropy.elements[0] < 0.0 && cross_entropy.elements[1] > 0.0);
+    assert!(cross_entropy.sum().abs() < 1e-6);
+
+    assert_eq!(MseLoss.value(&input, &target), network.mse_loss(input.clone(), target.clone()));
+    assert_eq!(MaeLoss.value(&input, &target), 1.125);
+    let mut trained = network.clone();
+    trained.train_with_loss(input.clone(), target.clone(), 0.1, &CrossEntropyLoss).unwrap();
+    assert!(trained.cross_entropy_loss(input.clone(), target.clone()) < network.cross_entropy_loss(input, target));
+}
+
+#[test]
+fn test_ensemble_predict_averages_outputs() {
+    let first = Network::new(vec![Layer::new(Matrix::from_vec(vec![vec![1.0, 2.0], vec![0.0, 1.0]]), Vector::new(vec![0.5, 0.0]))]);
+    let second = Network::new(vec![Layer::new(Matrix::from_vec(vec![vec![3.0, 0.0], vec![1.0, -1.0]]), Vector::new(vec![0.0, 1.5]))]);
+    let input = Vector::new(vec![1.0, 2.0]);
+    let (a, b) = (first.forward(input.clone()), second.forward(input.clone()));
+    let networks = [first, second];
+
+    let mean = ensemble_predict(&networks, input.clone()).unwrap();
+    assert_eq!(mean, a.add(&b).unwrap().scalar_multiply(0.5));
+    let weighted = weighted_ensemble_predict(&networks, &[3.0, 1.0], input.clone()).unwrap();
+    assert!(weighted.approx_eq(&a.scalar_multiply(0.75).add(&b.scalar_multiply(0.25)).unwrap(), 1e-6));
+
+    let narrow = Network::new(vec![Layer::new(Matrix::ones(2, 1), Vector::zeros(1))]);
+    let mismatched = [networks[0].clone(), narrow];
+    assert_eq!(ensemble_predict(&mismatched, input.clone()).unwrap_err(), "Network 1 has 1 outputs, but network 0 has 2");
+    assert!(ensemble_predict(&[], input.clone()).is_err());
+    assert!(weighted_ensemble_predict(&networks, &[1.0], input).is_err());
+}
+
+#[test]
+fn test_network_train_stream_from_lazy_iterator() {
+    // Fit y = 2x - 1 from samples generated on demand, never held together.
+    let generated = std::cell::Cell::new(0);
+    let samples = (0..10_000).map(|i| {
+        generated.set(generated.get() + 1);
+        let x = (i % 100) as f32 / 50.0 - 1.0;
+        (Vector::new(vec![x]), Vector::new(vec![2.0 * x - 1.0]))
+    });
+
+    let mut network = Network::new(vec![Layer::new(Matrix::zeros(1, 1), Vector::zeros(1))]);
+    assert_eq!(generated.get(), 0);
+    assert_eq!(network.train_stream(samples, 0.01).unwrap(), 10_000);
+    assert_eq!(generated.get(), 10_000);
+    assert!(network.predict(Vector::new(vec![0.5])).approx_eq(&Vector::new(vec![0.0]), 1e-3));
+
+    let diverging = std::iter::repeat((Vector::new(vec![1e20]), Vector::new(vec![0.0])));
+    assert!(matches!(network.train_stream(diverging, 1.0), Err(NetworkError::NonFinite { epoch: None, layer: 0 })));
+}
+
+#[test]
+fn test_network_fit_records_history() {
+    let inputs: Vec<Vector> = (0..8).map(|i| Vector::new(vec![i as f32 / 8.0, 1.0 - i as f32 / 8.0])).collect();
+    let targets: Vec<Vector> = inputs.iter().map(|x| Vector::new(vec![x.elements[0]])).collect();
+    let mut network = Network::new_seeded(&[(2, 3), (3, 1)], 5);
+
+    let epochs = 20;
+    let history = network.fit(&inputs, &targets, 0.05, epochs).unwrap();
+    assert_eq!(history.loss.len(), epochs);
+    assert_eq!(history.accuracy.len(), epochs);
+    assert!(history.loss.iter().chain(&history.accuracy).all(|v| v.is_finite()));
+    assert!(history.loss[epochs - 1] < history.loss[0]);
+    assert_eq!(history.loss[epochs - 1], network.evaluate_batch(&inputs, &targets).0);
+}
+
+#[test]
+#[should_panic(expected = "Expected an input of dimension 3 for the first layer, got 2")]
+fn test_network_forward_rejects_wrong_input_dimension() {
+    Network::new_seeded(&[(3, 2)], 1).forward(Vector::new(vec![1.0, 2.0]));
+}
+
+#[test]
+#[should_panic(expected = "Expected an input of dimension 3 for the first layer, got 5")]
+fn test_network_forward_batch_rejects_wrong_input_dimension() {
+    Network::new_seeded(&[(3, 4), (4, 1)], 1).forward_batch(&Matrix::zeros(2, 5));
+}
+
+#[test]
+fn test_network_quantized_forward_tracks_float_forward() {
+    let network = Network::new(vec![
+        Layer::new_seeded(16, 32, 1).with_activation(Activation::Relu),
+        Layer::new_seeded(32, 32, 2).with_activation(Activation::Tanh).with_residual().unwrap(),
+        Layer::new_seeded(32, 4, 3).without_bias(),
+    ]);
+    let quantized = network.quantize_int8().unwrap();
+
+    for s in 0..20 {
+        let input = Vector::new((0..16).map(|i| ((s * 16 + i) as f32 * 0.37).sin()).collect());
+        let (expected, actual) = (network.forward(input.clone()), quantized.forward_quantized(input));
+        // Int8 weights carry about two significant digits, and three layers
+        // compound the rounding error.
+        assert!(actual.subtract(&expected).magnitude() < 0.05 * expected.magnitude(), "quantized {:?} vs float {:?}", actual, expected);
+    }
+
+    let float_bytes = network.num_parameters() * std::mem::size_of::<f32>();
+    let ratio = quantized.size_bytes() as f32 / float_bytes as f32;
+    assert!(ratio > 0.25 && ratio < 0.3, "quantized model is {} of the float size", ratio);
+
+    let kan = Network::new(vec![Layer::kan(2, 1, 3, 3)]);
+    assert_eq!(kan.quantize_int8().unwrap_err().to_string(), "Export error: Layer 0 has edge splines, which int8 quantization does not support");
+}
+
+#[test]
+fn test_network_shuffles_samples_each_epoch() {
+    let inputs: Vec<Vector> = (0..10).map(|i| Vector::new(vec![i as f32 / 10.0])).collect();
+    let targets = inputs.clone();
+    let visit_epochs = |network: &mut Network| -> Vec<Vec<usize>> {
+        (0..2)
+            .map(|_| {
+                let mut order = Vec::new();
+                network.train_epoch_with_callback(&inputs, &targets, 0.01, |index| order.push(index)).unwrap();
+                order
+            })
+            .collect()
+    };
+
+    let mut sequential = Network::new_seeded(&[(1, 1)], 1);
+    assert_eq!(visit_epochs(&mut sequential), vec![(0..10).collect::<Vec<_>>(); 2]);
+
+    let mut shuffled = Network::new_seeded(&[(1, 1)], 1).with_shuffle_seed(42);
+    let orders = visit_epochs(&mut shuffled);
+    assert_ne!(orders[0], orders[1]);
+    for order in &orders {
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+    let mut reseeded = Network::new_seeded(&[(1, 1)], 1).with_shuffle_seed(42);
+    assert_eq!(visit_epochs(&mut reseeded), orders);
+}
+
+#[test]
+fn test_network_to_dot() {
+    let mut network = Network::new(vec![Layer::new(Matrix::ones(2, 3), Vector::zeros(3)), Layer::kan(3, 1, 3, 3)]);
+    network.layers[0].weights.rows[1].elements[2] = -0.5;
+    let dot = network.to_dot();
+    assert!(dot.starts_with("digraph network {"));
+    assert_eq!(dot.matches(" -> ").count(), 2 * 3 + 3);
+    assert!(dot.contains("    { rank=same; x0; x1; }\n"));
+    assert!(dot.contains("    x1 -> h1_2;\n"));
+    assert!(dot.contains("    h1_0 -> y0;\n"));
+
+    let labeled = network.to_dot_with_weights();
+    assert_eq!(labeled.matches(" [label=").count(), 9);
+    assert!(labeled.contains("    x1 -> h1_2 [label=\"0.50\"];\n"));
+}
+
+#[test]
+fn test_network_equivalent_tolerates_text_round_off() {
+    let network = Network::new_seeded(&[(3, 4), (4, 2)], 11);
+    // A model written by a tool that prints seven decimals.
+    let format_values = |values: &[f32]| values.iter().map(|v| format!("{:.7}", v)).collect::<Vec<_>>().join(",");
+    let text: String = network
+        .layers
+        .iter()
+        .map(|layer| {
+            let rows: Vec<String> = layer.weights.rows.iter().map(|row| format!("{{\"elements\":[{}]}}", format_values(&row.elements))).collect();
+            format!("Layer\n{{\"rows\":[{}]}}\n[{}]\n", rows.join(","), format_values(&layer.biases.elements))
+        })
+        .collect();
+    let loaded = Network::try_from_str(&text).unwrap();
+
+    assert_ne!(loaded, network);
+    assert!(loaded.equivalent(&network, 1e-6));
+    assert!(!loaded.equivalent(&network, 0.0));
+
+    let mut relu = loaded.clone();
+    relu.layers[1].activation = Activation::Relu;
+    assert!(!relu.equivalent(&network, 1e-6));
+    assert!(!Network::new_seeded(&[(3, 4)], 11).equivalent(&network, 1.0));
+}
@@ -0,0 +1,42 @@
+//! Runs in every feature configuration, including `--no-default-features`,
+//! to check that inference is available without the `std` feature. The test
+//! harness itself links `std`, so the `no_std` build proper is checked by CI
+//! building the library for a bare-metal target.
+
+use crate::data_structures::{Layer, Matrix, Spline, Vector};
+use crate::network::Network;
+use crate::utils::activations::Activation;
+
+#[test]
+fn test_core_forward_pass() {
+    let hidden = Layer::new(Matrix::new(vec![Vector::new(vec![1.0, -1.0]), Vector::new(vec![0.5, 2.0])]), Vector::new(vec![0.0, 1.0]))
+        .with_activation(Activation::Relu);
+    let mut output = Layer::new(Matrix::new(vec![Vector::new(vec![1.0]), Vector::new(vec![1.0])]), Vector::zeros(1));
+    output.splines = vec![vec![Spline::new(4, 3)], vec![Spline::new(4, 3)]];
+
+    // hidden: relu([1 + 0.5, -1 + 2 + 1]) = [1.5, 2]; the zero splines add nothing.
+    let x = Vector::new(vec![1.0, 1.0]);
+    let result = output.forward(&hidden.forward(&x));
+    assert_eq!(result, Vector::new(vec![3.5]));
+
+    let batch = Matrix::new(vec![x.clone(), x]);
+    assert_eq!(output.forward_batch(&hidden.forward_batch(&batch)).rows[0], result);
+}
+
+#[test]
+fn test_core_network_inference() {
+    let hidden = Layer::new(Matrix::new(vec![Vector::new(vec![1.0, -1.0]), Vector::new(vec![0.5, 2.0])]), Vector::new(vec![0.0, 1.0]))
+        .with_activation(Activation::Relu);
+    let output = Layer::new(Matrix::new(vec![Vector::new(vec![1.0]), Vector::new(vec![1.0])]), Vector::zeros(1));
+    let mut network = Network::new(vec![hidden, output]);
+
+    let x = Vector::new(vec![1.0, 1.0]);
+    assert_eq!(network.forward(x.clone()), Vector::new(vec![3.5]));
+    assert_eq!(network.predict(x.clone()), Vector::new(vec![3.5]));
+    let batch = Matrix::new(vec![x.clone(), Vector::new(vec![0.0, 0.0])]);
+    assert_eq!(network.forward_batch(&batch), Matrix::new(vec![Vector::new(vec![3.5]), Vector::new(vec![1.0])]));
+
+    // A single training step needs no `std` either.
+    network.train(x.clone(), Vector::new(vec![3.0]), 0.1).unwrap();
+    assert!(network.forward(x).elements[0] < 3.5);
+}
@@ -0,0 +1,138 @@
+use crate::data_structures::{Layer, Vector};
+use crate::network::{Network, NetworkError};
+use crate::utils::activations::Activation;
+use std::collections::HashMap;
+
+/// A decoded protobuf field: a varint or a length-delimited payload.
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> u64 {
+    let (mut value, mut shift) = (0, 0);
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+fn fields(bytes: &[u8]) -> Vec<(u64, Field<'_>)> {
+    let mut pos = 0;
+    let mut fields = Vec::new();
+    while pos < bytes.len() {
+        let key = read_varint(bytes, &mut pos);
+        let field = match key & 7 {
+            0 => Field::Varint(read_varint(bytes, &mut pos)),
+            2 => {
+                let len = read_varint(bytes, &mut pos) as usize;
+                pos += len;
+                Field::Bytes(&bytes[pos - len..pos])
+            }
+            wire_type => panic!("Unexpected wire type {}", wire_type),
+        };
+        fields.push((key >> 3, field));
+    }
+    fields
+}
+
+fn bytes_of(message: &[u8], number: u64) -> Vec<&[u8]> {
+    fields(message).into_iter().filter_map(|(n, f)| match f { Field::Bytes(b) if n == number => Some(b), _ => None }).collect()
+}
+
+fn varints_of(message: &[u8], number: u64) -> Vec<u64> {
+    fields(message).into_iter().filter_map(|(n, f)| match f { Field::Varint(v) if n == number => Some(v), _ => None }).collect()
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Evaluates the decoded graph on a single sample, supporting the operators
+/// that `to_onnx` emits.
+fn run_onnx(model: &[u8], input: &Vector) -> Vector {
+    let graph = bytes_of(model, 7)[0];
+    let mut values: HashMap<String, (Vec<usize>, Vec<f32>)> = HashMap::new();
+    values.insert("input".to_string(), (vec![1, input.len()], input.elements.clone()));
+    for tensor in bytes_of(graph, 5) {
+        let dims = varints_of(tensor, 1).into_iter().map(|d| d as usize).collect();
+        let data = bytes_of(tensor, 9)[0].chunks(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect();
+        values.insert(text(bytes_of(tensor, 8)[0]), (dims, data));
+    }
+
+    for node in bytes_of(graph, 1) {
+        let inputs: Vec<(Vec<usize>, Vec<f32>)> = bytes_of(node, 1).into_iter().map(|name| values[&text(name)].clone()).collect();
+        let (shape, a) = inputs[0].clone();
+        let result = match text(bytes_of(node, 4)[0]).as_str() {
+            "MatMul" => {
+                let (dims, w) = &inputs[1];
+                let result = (0..dims[1]).map(|j| (0..dims[0]).map(|i| a[i] * w[i * dims[1] + j]).sum()).collect();
+                (vec![1, dims[1]], result)
+            }
+            "Add" => (shape, a.iter().zip(&inputs[1].1).map(|(x, y)| x + y).collect()),
+            "Relu" => (shape, a.iter().map(|x| x.max(0.0)).collect()),
+            "Sigmoid" => (shape, a.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect()),
+            "Tanh" => (shape, a.iter().map(|x| x.tanh()).collect()),
+            "Identity" => (shape, a),
+            op_type => panic!("Unexpected operator {}", op_type),
+        };
+        values.insert(text(bytes_of(node, 2)[0]), result);
+    }
+    Vector::new(values["output"].1.clone())
+}
+
+fn network() -> Network {
+    Network::new(vec![
+        Layer::new_seeded(3, 4, 1).with_activation(Activation::Relu),
+        Layer::new_seeded(4, 4, 2).with_activation(Activation::Tanh).with_residual().unwrap(),
+        Layer::new_seeded(4, 2, 3).with_activation(Activation::Sigmoid).without_bias(),
+    ])
+}
+
+#[test]
+fn test_onnx_round_trip_matches_forward() {
+    let network = network();
+    let model = network.to_onnx().unwrap();
+    assert_eq!(varints_of(&model, 1), vec![7]);
+    assert_eq!(varints_of(bytes_of(&model, 8)[0], 2), vec![13]);
+
+    for input in [Vector::new(vec![0.5, -1.0, 2.0]), Vector::new(vec![-0.3, 0.1, 0.0]), Vector::zeros(3)] {
+        assert!(run_onnx(&model, &input).approx_eq(&network.forward(input), 1e-5));
+    }
+}
+
+#[test]
+fn test_onnx_export_rejects_unsupported_layers() {
+    let gelu = Network::new(vec![Layer::new_seeded(2, 2, 1).with_activation(Activation::Gelu)]);
+    assert!(matches!(gelu.to_onnx(), Err(NetworkError::Export(_))));
+    let kan = Network::new(vec![Layer::kan(2, 1, 3, 3)]);
+    assert_eq!(kan.to_onnx().unwrap_err().to_string(), "Export error: Layer 0 has edge splines, which ONNX export does not support");
+}
+
+/// Checks the exported file with onnxruntime. Needs `python3` with the
+/// `onnxruntime` and `numpy` packages: run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_onnx_export_loads_in_onnxruntime() {
+    let network = network();
+    let path = std::env::temp_dir().join("kan_export_test.onnx");
+    network.export_onnx(path.to_str().unwrap()).unwrap();
+
+    let input = Vector::new(vec![0.5, -1.0, 2.0]);
+    let script = format!(
+        "import numpy, onnxruntime\n\
+         session = onnxruntime.InferenceSession({:?})\n\
+         print(*session.run(None, {{'input': numpy.array([{:?}], dtype=numpy.float32)}})[0][0])",
+        path.to_str().unwrap(),
+        input.elements
+    );
+    let output = std::process::Command::new("python3").args(["-c", &script]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let values: Vec<f32> = String::from_utf8(output.stdout).unwrap().split_whitespace().map(|v| v.parse().unwrap()).collect();
+    assert!(Vector::new(values).approx_eq(&network.forward(input), 1e-5));
+}
@@ -0,0 +1,83 @@
+use crate::data_structures::{Layer, Matrix, Vector};
+use crate::network::{Adam, Network, Optimizer, Sgd};
+
+fn train_loss(optimizer: &mut dyn Optimizer, epochs: usize) -> f32 {
+    let mut network = Network::new(vec![
+        Layer::new(Matrix::zeros(1, 2), Vector::zeros(2)),
+        Layer::new(Matrix::from_vec(vec![vec![0.5], vec![-0.5]]), Vector::zeros(1)),
+    ]);
+    let input = Vector::new(vec![0.1]);
+    let target = Vector::new(vec![1.0]);
+    for _ in 0..epochs {
+        network.train_with_optimizer(input.clone(), target.clone(), optimizer).unwrap();
+    }
+    network.mse_loss(input, target)
+}
+
+#[test]
+fn test_sgd_matches_update() {
+    let mut network = Network::new(vec![Layer::random(2, 2)]);
+    let mut expected = network.clone();
+    let input = Vector::new(vec![1.0, 2.0]);
+    let target = Vector::new(vec![0.0, 1.0]);
+
+    network.train_with_optimizer(input.clone(), target.clone(), &mut Sgd::new(0.1)).unwrap();
+    expected.train(input, target, 0.1).unwrap();
+    assert_eq!(network, expected);
+}
+
+#[test]
+fn test_adam_converges_faster_than_sgd() {
+    let sgd_loss = train_loss(&mut Sgd::new(0.01), 100);
+    let adam_loss = train_loss(&mut Adam::new(0.01), 100);
+    assert!(adam_loss < sgd_loss);
+}
+
+#[test]
+fn test_sgd_momentum_converges_faster() {
+    let plain_loss = train_loss(&mut Sgd::new(0.01), 100);
+    let momentum_loss = train_loss(&mut Sgd::new(0.01).with_momentum(0.9), 100);
+    assert!(momentum_loss < plain_loss, "momentum {} vs plain {}", momentum_loss, plain_loss);
+}
+
+#[test]
+fn test_sgd_zero_momentum_matches_plain_sgd() {
+    assert_eq!(train_loss(&mut Sgd::new(0.01).with_momentum(0.0), 20), train_loss(&mut Sgd::new(0.01), 20));
+}
+
+#[test]
+fn test_optimizer_trains_splines() {
+    let network = Network::new(vec![Layer::kan(2, 1, 4, 3)]);
+    let (input, target) = (Vector::new(vec![0.3, -0.6]), Vector::new(vec![1.0]));
+    for optimizer in [&mut Sgd::new(0.1) as &mut dyn Optimizer, &mut Adam::new(0.1)] {
+        let mut trained = network.clone();
+        trained.train_with_optimizer(input.clone(), target.clone(), optimizer).unwrap();
+        assert_ne!(trained.layers[0].splines, network.layers[0].splines);
+    }
+
+    // Plain SGD matches `train`, splines included.
+    let (mut sgd, mut expected) = (network.clone(), network);
+    sgd.train_with_optimizer(input.clone(), target.clone(), &mut Sgd::new(0.1)).unwrap();
+    expected.train(input, target, 0.1).unwrap();
+    assert_eq!(sgd, expected);
+}
+
+#[test]
+fn test_optimizer_applies_weight_decay_and_l1() {
+    let (input, target) = (Vector::new(vec![0.0, 0.0]), Vector::new(vec![0.0]));
+    let weights = Matrix::from_vec(vec![vec![2.0], vec![-1.0]]);
+    // With a zero input and target the loss gradient vanishes, so only the
+    // penalties move the weights.
+    let mut decayed = Network::new(vec![Layer::new(weights.clone(), Vector::zeros(1))]).with_weight_decay(0.5);
+    decayed.train_with_optimizer(input.clone(), target.clone(), &mut Sgd::new(0.1)).unwrap();
+    assert!(decayed.layers[0].weights.approx_eq(&Matrix::from_vec(vec![vec![1.9], vec![-0.95]]), 1e-6));
+
+    let mut sparse = Network::new(vec![Layer::new(weights, Vector::zeros(1))]).with_l1(0.5);
+    sparse.train_with_optimizer(input.clone(), target.clone(), &mut Sgd::new(0.1)).unwrap();
+    assert!(sparse.layers[0].weights.approx_eq(&Matrix::from_vec(vec![vec![1.95], vec![-0.95]]), 1e-6));
+
+    let mut adam = Network::new(vec![Layer::new(Matrix::from_vec(vec![vec![2.0], vec![-1.0]]), Vector::zeros(1))]).with_weight_decay(0.5);
+    adam.train_with_optimizer(input, target, &mut Adam::new(0.1)).unwrap();
+    assert!(adam.layers[0].weights.rows.iter().all(|row| row.elements[0].abs() < 2.0));
+    assert!(adam.layers[0].weights.approx_eq(&Matrix::from_vec(vec![vec![1.9], vec![-0.9]]), 1e-4));
+}
@@ -0,0 +1,58 @@
+use crate::data_structures::{Layer, Matrix, Vector};
+use crate::network::{LrSchedule, Network};
+
+fn is_close_enough(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-6
+}
+
+#[test]
+fn test_constant_schedule() {
+    let schedule = LrSchedule::Constant(0.1);
+    for epoch in [0, 10, 20] {
+        assert_eq!(schedule.lr_at(epoch), 0.1);
+    }
+}
+
+#[test]
+fn test_step_decay_schedule() {
+    let schedule = LrSchedule::StepDecay { initial: 0.1, gamma: 0.5, step_size: 10 };
+    assert!(is_close_enough(schedule.lr_at(0), 0.1));
+    assert!(is_close_enough(schedule.lr_at(9), 0.1));
+    assert!(is_close_enough(schedule.lr_at(10), 0.05));
+    assert!(is_close_enough(schedule.lr_at(20), 0.025));
+}
+
+#[test]
+fn test_exponential_decay_schedule() {
+    let schedule = LrSchedule::ExponentialDecay { initial: 0.1, gamma: 0.9 };
+    assert!(is_close_enough(schedule.lr_at(0), 0.1));
+    assert!(is_close_enough(schedule.lr_at(10), 0.1 * 0.9f32.powi(10)));
+    assert!(is_close_enough(schedule.lr_at(20), 0.1 * 0.9f32.powi(20)));
+}
+
+#[test]
+fn test_cosine_annealing_schedule() {
+    let schedule = LrSchedule::CosineAnnealing { lr_max: 0.1, lr_min: 0.01, period: 10 };
+    for restart in [0, 10, 20] {
+        assert!(is_close_enough(schedule.lr_at(restart), 0.1));
+    }
+    // Halfway through a cycle the rate is midway between the bounds, and it
+    // approaches `lr_min` at the end of the cycle.
+    assert!(is_close_enough(schedule.lr_at(5), 0.055));
+    assert!(is_close_enough(schedule.lr_at(15), 0.055));
+    let end = schedule.lr_at(9);
+    assert!(end > 0.01 && end < 0.0135);
+    assert!((1..10).all(|epoch| schedule.lr_at(epoch) < schedule.lr_at(epoch - 1)));
+}
+
+#[test]
+fn test_train_with_constant_schedule_matches_train_epochs() {
+    let inputs = vec![Vector::new(vec![0.5, -1.0]), Vector::new(vec![1.0, 0.25])];
+    let targets = vec![Vector::new(vec![1.0]), Vector::new(vec![0.0])];
+    let mut scheduled = Network::new(vec![Layer::new(Matrix::ones(2, 1), Vector::zeros(1))]);
+    let mut fixed = scheduled.clone();
+
+    scheduled.train_epochs_with_schedule(&inputs, &targets, &LrSchedule::Constant(0.1), 5).unwrap();
+    fixed.train_epochs(&inputs, &targets, 0.1, 5).unwrap();
+    assert_eq!(scheduled, fixed);
+}
@@ -0,0 +1,216 @@
+use crate::data_structures::{Layer, Matrix, Spline, SymbolicFn, Vector};
+
+fn cubic_spline(control_points: Vec<f32>) -> Spline {
+    let knots = (0..8).map(|i| i as f32).collect();
+    Spline::from_knots(knots, control_points, 3).unwrap()
+}
+
+#[test]
+fn test_spline_cubic_uniform_values() {
+    let spline = cubic_spline(vec![1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(spline.domain(), (3.0, 4.0));
+
+    // Uniform cubic basis at the knot: [1/6, 4/6, 1/6, 0].
+    let basis = spline.basis_values(3.0);
+    let expected = [1.0 / 6.0, 4.0 / 6.0, 1.0 / 6.0, 0.0];
+    for (b, e) in basis.iter().zip(expected) {
+        assert!((b - e).abs() < 1e-6);
+    }
+    assert!((spline.evaluate(3.0) - 2.0).abs() < 1e-6);
+
+    // At the segment midpoint: [1/48, 23/48, 23/48, 1/48].
+    assert!((spline.evaluate(3.5) - 2.5).abs() < 1e-6);
+    let bump = cubic_spline(vec![0.0, 0.0, 6.0, 0.0]);
+    assert!((bump.evaluate(3.5) - 2.875).abs() < 1e-6);
+}
+
+#[test]
+fn test_spline_partition_of_unity() {
+    let spline = Spline::uniform(5, 3, -1.0, 1.0);
+    for i in 0..20 {
+        let x = -1.0 + i as f32 * 0.1;
+        let total: f32 = spline.basis_values(x).iter().sum();
+        assert!((total - 1.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_spline_derivative_matches_finite_difference() {
+    let spline = cubic_spline(vec![1.0, -2.0, 0.5, 3.0]);
+    let h = 1e-3;
+    for &x in &[3.1, 3.4, 3.7] {
+        let numeric = (spline.evaluate(x + h) - spline.evaluate(x - h)) / (2.0 * h);
+        assert!((spline.derivative(x) - numeric).abs() < 1e-2);
+    }
+}
+
+#[test]
+fn test_spline_from_knots_length_mismatch() {
+    assert!(Spline::from_knots(vec![0.0, 1.0, 2.0], vec![1.0, 2.0], 3).is_err());
+}
+
+#[test]
+fn test_layer_forward_sums_edge_splines() {
+    let mut layer = Layer::new(Matrix::zeros(2, 1), Vector::zeros(1));
+    layer.splines = vec![
+        vec![cubic_spline(vec![1.0, 2.0, 3.0, 4.0])],
+        vec![cubic_spline(vec![0.0, 0.0, 6.0, 0.0])],
+    ];
+
+    let output = layer.forward(&Vector::new(vec![3.0, 3.5]));
+    assert!((output.get_element(0) - (2.0 + 2.875)).abs() < 1e-5);
+}
+
+#[test]
+fn test_spline_refine_grid_preserves_shape() {
+    let mut spline = Spline::random(5, 3, -1.0, 1.0);
+    let original = spline.clone();
+
+    spline.refine_grid(10);
+    assert_eq!(spline.control_points.len(), 13);
+    assert_eq!(spline.domain(), original.domain());
+    for i in 0..=100 {
+        let x = -1.0 + i as f32 * 0.02;
+        assert!((spline.evaluate(x) - original.evaluate(x)).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_network_refine_grid() {
+    let mut network = crate::network::Network::new(vec![Layer::kan(2, 2, 4, 3)]);
+    let input = Vector::new(vec![0.3, -0.7]);
+    let before = network.forward(input.clone());
+
+    network.refine_grid(8);
+    assert!(network.layers[0].splines.iter().flatten().all(|spline| spline.control_points.len() == 11));
+    let after = network.forward(input);
+    for (a, b) in before.elements.iter().zip(&after.elements) {
+        assert!((a - b).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_spline_fit_symbolic_recovers_sine() {
+    let spline = Spline::fit(10, 3, -3.0, 3.0, f32::sin);
+    assert!((spline.evaluate(1.0) - 1.0f32.sin()).abs() < 1e-2);
+
+    let (function, r2) = spline.fit_symbolic(&SymbolicFn::ALL).unwrap();
+    assert_eq!(function, SymbolicFn::Sin);
+    assert!(r2 > 0.999, "r2 {}", r2);
+
+    let (function, _) = Spline::fit(10, 3, -1.0, 1.0, |x| 2.0 * x * x + 1.0).fit_symbolic(&SymbolicFn::ALL).unwrap();
+    assert_eq!(function, SymbolicFn::Square);
+}
+
+#[test]
+fn test_network_to_symbolic() {
+    let mut layer = Layer::new(Matrix::from_vec(vec![vec![0.0, 3.0]]), Vector::zeros(2));
+    layer.splines = vec![vec![Spline::fit(10, 3, -3.0, 3.0, f32::sin), Spline::uniform(5, 3, -1.0, 1.0)]];
+    let formulas = crate::network::Network::new(vec![layer]).to_symbolic();
+
+    assert_eq!(formulas.len(), 1);
+    assert_eq!(formulas[0][0].replace("-0.0000", "0.0000"), "1.0000 * sin(x0) + 0.0000");
+    assert!(formulas[0][1].starts_with("3.0000 * x0"), "{}", formulas[0][1]);
+}
+
+#[test]
+fn test_spline_new_uses_default_domain() {
+    let spline = Spline::new(4, 2);
+    assert_eq!(spline.degree, 2);
+    assert_eq!(spline.control_points.len(), 6);
+    assert_eq!(spline.domain(), (-1.0, 1.0));
+}
+
+#[test]
+fn test_spline_linear_values() {
+    let knots = (0..5).map(|i| i as f32).collect();
+    let spline = Spline::from_knots(knots, vec![1.0, 3.0, 2.0], 1).unwrap();
+    assert_eq!(spline.domain(), (1.0, 3.0));
+    // Degree 1 interpolates linearly between control points placed at the knots.
+    assert!((spline.evaluate(1.0) - 1.0).abs() < 1e-6);
+    assert!((spline.evaluate(1.5) - 2.0).abs() < 1e-6);
+    assert!((spline.evaluate(2.5) - 2.5).abs() < 1e-6);
+    assert!((spline.derivative(1.5) - 2.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_spline_quadratic_values() {
+    let knots = (0..6).map(|i| i as f32).collect();
+    let spline = Spline::from_knots(knots, vec![0.0, 1.0, 0.0], 2).unwrap();
+    assert_eq!(spline.domain(), (2.0, 3.0));
+    // Uniform quadratic basis: [1/2, 1/2, 0] at a knot, [1/8, 6/8, 1/8] mid-segment.
+    assert!((spline.evaluate(2.0) - 0.5).abs() < 1e-6);
+    assert!((spline.evaluate(2.5) - 0.75).abs() < 1e-6);
+}
+
+#[test]
+fn test_layer_spline_gradients_match_finite_differences() {
+    let layer = Layer::kan(2, 2, 4, 3);
+    let (input, delta) = (Vector::new(vec![0.3, -0.6]), Vector::new(vec![1.0, -0.5]));
+    let gradients = layer.spline_gradients(&input, &delta);
+    assert_eq!(gradients.len(), 2);
+
+    // The gradient of `delta · output` with respect to each control point.
+    let objective = |layer: &Layer| layer.forward(&input).dot(&delta).unwrap();
+    let epsilon = 1e-2;
+    for (i, j, k) in [(0, 0, 2), (1, 1, 3), (0, 1, 4)] {
+        let (mut plus, mut minus) = (layer.clone(), layer.clone());
+        plus.splines[i][j].control_points[k] += epsilon;
+        minus.splines[i][j].control_points[k] -= epsilon;
+        let numeric = (objective(&plus) - objective(&minus)) / (2.0 * epsilon);
+        assert!((gradients[i][j][k] - numeric).abs() < 1e-3, "{} vs {}", gradients[i][j][k], numeric);
+    }
+}
+
+#[test]
+fn test_network_trains_spline_control_points() {
+    let mut layer = Layer::new(Matrix::zeros(1, 1), Vector::zeros(1));
+    layer.splines = vec![vec![Spline::new(8, 3)]];
+    let mut network = crate::network::Network::new(vec![layer]);
+    let inputs: Vec<Vector> = (0..21).map(|i| Vector::new(vec![i as f32 / 10.0 - 1.0])).collect();
+    let targets: Vec<Vector> = inputs.iter().map(|x| Vector::new(vec![x.elements[0] * x.elements[0]])).collect();
+
+    let before = network.loss_batch(&inputs, &targets);
+    network.train_epochs(&inputs, &targets, 0.5, 200).unwrap();
+    assert!(network.layers[0].splines[0][0].control_points.iter().any(|&c| c != 0.0));
+    assert!(network.loss_batch(&inputs, &targets) < 0.1 * before);
+}
+
+#[test]
+fn test_spline_extend_grid() {
+    let mut spline = Spline::fit(5, 3, -1.0, 1.0, |x| 2.0 * x + 1.0);
+    let original = spline.clone();
+    // Far outside the knots every basis function vanishes.
+    assert_eq!(spline.evaluate(2.5), 0.0);
+
+    spline.extend_grid(-3.0, 3.0);
+    let (min, max) = spline.domain();
+    assert!(min <= -3.0 && max >= 3.0);
+    for i in 0..=100 {
+        let x = -1.0 + i as f32 * 0.02;
+        assert!((spline.evaluate(x) - original.evaluate(x)).abs() < 1e-5);
+    }
+    // A linear fit extends linearly, and continuously across the old boundary.
+    for x in [-2.8, -1.5, 1.5, 2.5] {
+        assert!((spline.evaluate(x) - (2.0 * x + 1.0)).abs() < 1e-3, "{} at {}", spline.evaluate(x), x);
+    }
+    assert!((spline.evaluate(0.999) - spline.evaluate(1.001)).abs() < 1e-2);
+
+    // Extending to a range already covered changes nothing.
+    let extended = spline.clone();
+    spline.extend_grid(-1.0, 1.0);
+    assert_eq!(spline, extended);
+}
+
+#[test]
+#[should_panic(expected = "Cannot extend the grid of a degree 3 spline with 3 control points; it needs at least 4")]
+fn test_spline_extend_grid_rejects_empty_grid() {
+    Spline::uniform(0, 3, -1.0, 1.0).extend_grid(-2.0, 2.0);
+}
+
+#[test]
+#[should_panic(expected = "Cannot extend the grid of a degree 3 spline with 2 control points; it needs at least 4")]
+fn test_spline_extend_grid_rejects_too_few_control_points() {
+    let knots = (0..6).map(|i| i as f32).collect();
+    Spline::from_knots(knots, vec![1.0, 2.0], 3).unwrap().extend_grid(-2.0, 8.0);
+}
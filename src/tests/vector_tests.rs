@@ -1,15 +1,156 @@
 use crate::data_structures::Vector;
 
 #[test]
+#[allow(clippy::excessive_precision)]
 fn test_vector_operations() {
-    let v1 = Vector::new(vec![1.0, 2.0, 3.0]);
+    let v1: Vector = Vector::new(vec![1.0, 2.0, 3.0]);
     let v2 = Vector::new(vec![4.0, 5.0, 6.0]);
 
-    assert_eq!(v1.add(&v2), Vector::new(vec![5.0, 7.0, 9.0]));
+    assert_eq!(v1.add(&v2), Ok(Vector::new(vec![5.0, 7.0, 9.0])));
     assert_eq!(v1.subtract(&v2), Vector::new(vec![-3.0, -3.0, -3.0]));
-    assert_eq!(v1.dot(&v2), 32.0);
+    assert_eq!(v1.dot(&v2), Ok(32.0));
     // assert_eq!(v1.cross(&v2), Vector::new(vec![20.0, -15.0, 10.0]));
     assert_eq!(v1.magnitude(), 3.7416573867739413);
     assert_eq!(v1.normalize(), Vector::new(vec![0.2672612419124244, 0.5345224838248488, 0.8017837]));
 
 }
+
+
+#[test]
+fn test_vector_dot() {
+    let x = Vector::new(vec![1.0, 0.0]);
+    let y = Vector::new(vec![0.0, 3.0]);
+    assert_eq!(x.dot(&y), Ok(0.0));
+
+    let v: Vector = Vector::new(vec![1.0, 2.0, 2.0]);
+    let parallel = v.scalar_multiply(2.0);
+    assert_eq!(v.dot(&parallel), Ok(18.0));
+    assert_eq!(v.dot(&v).unwrap().sqrt(), v.magnitude());
+
+    assert!(x.dot(&v).is_err());
+}
+
+#[test]
+fn test_vector_normalize() {
+    let unit = Vector::new(vec![0.0, 1.0, 0.0]);
+    assert_eq!(unit.normalize(), unit);
+
+    let zero: Vector = Vector::zeros(3);
+    assert_eq!(zero.normalize(), zero);
+
+    let v: Vector = Vector::new(vec![3.0, 4.0]);
+    assert!((v.normalize().magnitude() - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_vector_mean_std_and_scale_to_unit() {
+    let v = Vector::new(vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+    assert_eq!(v.mean(), 5.0);
+    assert_eq!(v.std(), 2.0);
+
+    let scaled = Vector::new(vec![-1.0, 0.0, 3.0]).scale_to_unit();
+    assert_eq!(scaled, Vector::new(vec![0.0, 0.25, 1.0]));
+    assert_eq!(Vector::<f32>::ones(2).scale_to_unit(), Vector::zeros(2));
+}
+
+#[test]
+fn test_vector_softmax() {
+    let probabilities: Vector = Vector::new(vec![1.0, 2.0, 3.0]).softmax();
+    let expected = [0.090_030_57, 0.244_728_48, 0.665_240_96];
+    for (p, e) in probabilities.elements.iter().zip(expected) {
+        assert!((p - e).abs() < 1e-6);
+    }
+    assert!((probabilities.sum() - 1.0).abs() < 1e-6);
+
+    // Large logits must not overflow.
+    let stable = Vector::new(vec![1000.0, 1000.0]).softmax();
+    assert_eq!(stable, Vector::new(vec![0.5, 0.5]));
+}
+
+#[test]
+fn test_vector_string_round_trip() {
+    let v = Vector::new(vec![1.0, -0.1, 3.25e-7]);
+    assert_eq!(Vector::from_string(&v.to_string()), Ok(v));
+    assert_eq!(Vector::from_string("1, 2"), Ok(Vector::new(vec![1.0, 2.0])));
+    assert_eq!(Vector::from_string("[]"), Ok(Vector::new(vec![])));
+    assert!(Vector::from_string("[1.0, x]").is_err());
+}
+
+#[test]
+fn test_vector_slice_and_vec_conversions() {
+    let values = [1.0, -2.0, 3.5];
+    let vector = Vector::from_slice(&values);
+    assert_eq!(vector.to_vec(), values.to_vec());
+
+    let vector: Vector = values.to_vec().into();
+    assert_eq!(vector, Vector::from_slice(&values));
+    let back: Vec<f32> = vector.into();
+    assert_eq!(back, values);
+}
+
+#[test]
+fn test_vector_add_length_mismatch() {
+    let v = Vector::new(vec![1.0, 2.0]);
+    assert_eq!(v.add(&Vector::new(vec![0.5, -4.0])).unwrap(), Vector::new(vec![1.5, -2.0]));
+    assert_eq!(v.add(&Vector::ones(3)).unwrap_err(), "Vector lengths do not match for addition: 2 and 3");
+}
+
+#[test]
+fn test_vector_concat_and_slice() {
+    let a = Vector::new(vec![1.0, 2.0]);
+    let b = Vector::new(vec![3.0, 4.0, 5.0]);
+    let joined = a.concat(&b);
+    assert_eq!(joined, Vector::new(vec![1.0, 2.0, 3.0, 4.0, 5.0]));
+    assert_eq!(joined.slice(1, 4), Vector::new(vec![2.0, 3.0, 4.0]));
+    assert_eq!(joined.slice(0, 2), a);
+    assert!(joined.slice(2, 2).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Slice range 3..6 out of bounds for vector of length 5")]
+fn test_vector_slice_out_of_range() {
+    Vector::<f32>::ones(5).slice(3, 6);
+}
+
+#[test]
+fn test_vector_approx_eq() {
+    let v = Vector::new(vec![1.0, 2.0, 3.0]);
+    assert!(v.approx_eq(&Vector::new(vec![1.0 + 1e-7, 2.0, 3.0 - 1e-7]), 1e-6));
+    assert!(!v.approx_eq(&Vector::new(vec![1.0, 2.1, 3.0]), 1e-6));
+    assert!(!v.approx_eq(&Vector::new(vec![1.0, 2.0]), 1e-6));
+}
+
+#[test]
+fn test_vector_argmax_argmin() {
+    let v = Vector::new(vec![2.0, -1.0, 5.0, 5.0, -1.0]);
+    assert_eq!(v.argmax(), 2);
+    assert_eq!(v.argmin(), 1);
+
+    let single = Vector::new(vec![3.0]);
+    assert_eq!(single.argmax(), 0);
+    assert_eq!(single.argmin(), 0);
+}
+
+#[test]
+fn test_vector_clamp() {
+    let v = Vector::new(vec![-2.0, -0.5, 0.0, 0.5, 3.0]);
+    assert_eq!(v.clamp(-1.0, 1.0), Vector::new(vec![-1.0, -0.5, 0.0, 0.5, 1.0]));
+    assert_eq!(v.clamp(0.25, 0.25), Vector::new(vec![0.25; 5]));
+}
+
+#[test]
+#[should_panic(expected = "Clamp bounds must satisfy min <= max, got 1 > 0")]
+fn test_vector_clamp_rejects_inverted_bounds() {
+    Vector::<f32>::zeros(2).clamp(1.0, 0.0);
+}
+
+#[test]
+fn test_vector_cosine_similarity() {
+    let a: Vector = Vector::new(vec![1.0, 2.0, -2.0]);
+    assert!((a.cosine_similarity(&a).unwrap() - 1.0).abs() < 1e-6);
+    assert!((a.cosine_similarity(&a.scalar_multiply(-3.0)).unwrap() + 1.0).abs() < 1e-6);
+    assert_eq!(a.cosine_similarity(&Vector::new(vec![2.0, 1.0, 2.0])).unwrap(), 0.0);
+
+    assert_eq!(a.cosine_similarity(&Vector::zeros(3)).unwrap_err(), "Cosine similarity is undefined for a zero vector");
+    assert!(a.cosine_similarity(&Vector::zeros(2)).is_err());
+}
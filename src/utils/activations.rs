@@ -1,3 +1,8 @@
+use crate::data_structures::{Float, Vector};
+use serde::{Deserialize, Serialize};
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float as _;
+
 pub fn relu(x: f64) -> f64 {
   if x > 0.0 { x } else { 0.0 }
 }
@@ -5,3 +10,62 @@ pub fn relu(x: f64) -> f64 {
 pub fn sigmoid(x: f64) -> f64 {
   1.0 / (1.0 + (-x).exp())
 }
+
+/// `sqrt(2 / π)`, the scale used by the tanh approximation of GELU.
+const GELU_SCALE: f64 = 0.797_884_560_802_865_4;
+const GELU_CUBIC: f64 = 0.044_715;
+
+/// The nonlinearity applied element-wise to the output of a layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Activation {
+    #[default]
+    Identity,
+    Relu,
+    Sigmoid,
+    Tanh,
+    /// The tanh approximation `0.5 x (1 + tanh(sqrt(2/π) (x + 0.044715 x³)))`.
+    Gelu,
+}
+
+impl Activation {
+    /// Applies the activation to every element of `v`.
+    pub fn apply<T: Float>(&self, v: &Vector<T>) -> Vector<T> {
+        v.map(|x| self.apply_scalar(x))
+    }
+
+    /// Evaluates the derivative of the activation at every element of `v`,
+    /// where `v` is the layer output before the activation is applied.
+    pub fn derivative<T: Float>(&self, v: &Vector<T>) -> Vector<T> {
+        v.map(|x| self.derivative_scalar(x))
+    }
+
+    fn apply_scalar<T: Float>(&self, x: T) -> T {
+        let (half, one) = (T::constant(0.5), T::one());
+        match self {
+            Activation::Identity => x,
+            Activation::Relu => x.max(T::zero()),
+            Activation::Sigmoid => one / (one + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::Gelu => half * x * (one + (T::constant(GELU_SCALE) * (x + T::constant(GELU_CUBIC) * x.powi(3))).tanh()),
+        }
+    }
+
+    fn derivative_scalar<T: Float>(&self, x: T) -> T {
+        let (half, one) = (T::constant(0.5), T::one());
+        match self {
+            Activation::Identity => one,
+            // The derivative at 0 is taken to be 0.
+            Activation::Relu => if x > T::zero() { one } else { T::zero() },
+            Activation::Sigmoid => {
+                let s = self.apply_scalar(x);
+                s * (one - s)
+            }
+            Activation::Tanh => one - x.tanh().powi(2),
+            Activation::Gelu => {
+                let (scale, cubic) = (T::constant(GELU_SCALE), T::constant(GELU_CUBIC));
+                let t = (scale * (x + cubic * x.powi(3))).tanh();
+                half * (one + t) + half * x * (one - t * t) * scale * (one + T::constant(3.0) * cubic * x * x)
+            }
+        }
+    }
+}
@@ -1,5 +1,101 @@
+use crate::data_structures::{Float, Vector};
+#[cfg(not(any(feature = "std", test)))]
+use num_traits::Float as _;
+
 pub fn mean_squared_error(predictions: &[f64], targets: &[f64]) -> f64 {
   predictions.iter().zip(targets.iter())
       .map(|(p, t)| (p - t).powi(2))
       .sum::<f64>() / predictions.len() as f64
 }
+
+/// The Huber loss averaged over the elements: `0.5 e²` for an error `e` within
+/// `delta`, and `delta (|e| - 0.5 delta)` beyond it.
+pub fn huber<T: Float>(output: &Vector<T>, target: &Vector<T>, delta: T) -> T {
+    let error = output.subtract(target);
+    let half = T::constant(0.5);
+    let total: T = error
+        .elements
+        .iter()
+        .map(|&e| if e.abs() <= delta { half * e * e } else { delta * (e.abs() - half * delta) })
+        .sum();
+    total / T::constant(error.len() as f64)
+}
+
+/// The gradient of the summed Huber loss with respect to the output: the
+/// error itself within `delta`, clipped to `±delta` beyond it.
+pub fn huber_gradient<T: Float>(output: &Vector<T>, target: &Vector<T>, delta: T) -> Vector<T> {
+    output.subtract(target).map(|e| e.max(-delta).min(delta))
+}
+
+/// A loss function comparing a network output with its target, and its
+/// gradient with respect to the output, which `Network::train_with_loss`
+/// backpropagates. The scalar type `T` defaults to `f32`.
+pub trait Loss<T: Float = f32> {
+    fn value(&self, output: &Vector<T>, target: &Vector<T>) -> T;
+    fn gradient(&self, output: &Vector<T>, target: &Vector<T>) -> Vector<T>;
+}
+
+/// The mean squared error. Its gradient is that of half the summed squared
+/// error, the error itself, matching `Network::backward`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MseLoss;
+
+impl<T: Float> Loss<T> for MseLoss {
+    fn value(&self, output: &Vector<T>, target: &Vector<T>) -> T {
+        let error = output.subtract(target);
+        error.dot(&error).unwrap() / T::constant(error.len() as f64)
+    }
+
+    fn gradient(&self, output: &Vector<T>, target: &Vector<T>) -> Vector<T> {
+        output.subtract(target)
+    }
+}
+
+/// The mean absolute error. Its gradient is the sign of the error, that of
+/// the summed absolute error, and zero where the output matches the target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaeLoss;
+
+impl<T: Float> Loss<T> for MaeLoss {
+    fn value(&self, output: &Vector<T>, target: &Vector<T>) -> T {
+        output.subtract(target).map(T::abs).sum() / T::constant(output.len() as f64)
+    }
+
+    fn gradient(&self, output: &Vector<T>, target: &Vector<T>) -> Vector<T> {
+        output.subtract(target).map(|e| if e > T::zero() { T::one() } else if e < T::zero() { -T::one() } else { T::zero() })
+    }
+}
+
+/// The cross-entropy between the softmax of the output and a one-hot (or
+/// probability) target, so the output holds logits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrossEntropyLoss;
+
+impl<T: Float> Loss<T> for CrossEntropyLoss {
+    fn value(&self, output: &Vector<T>, target: &Vector<T>) -> T {
+        let max = output.elements.iter().copied().fold(T::neg_infinity(), T::max);
+        let log_sum_exp = output.map(|x| (x - max).exp()).sum().ln() + max;
+        -output.elements.iter().zip(&target.elements).map(|(&z, &t)| t * (z - log_sum_exp)).sum::<T>()
+    }
+
+    /// `softmax(output) - target`, for a target summing to one.
+    fn gradient(&self, output: &Vector<T>, target: &Vector<T>) -> Vector<T> {
+        output.softmax().subtract(target)
+    }
+}
+
+/// The Huber loss with threshold `delta`; see `huber` and `huber_gradient`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HuberLoss {
+    pub delta: f32,
+}
+
+impl<T: Float> Loss<T> for HuberLoss {
+    fn value(&self, output: &Vector<T>, target: &Vector<T>) -> T {
+        huber(output, target, T::constant(f64::from(self.delta)))
+    }
+
+    fn gradient(&self, output: &Vector<T>, target: &Vector<T>) -> Vector<T> {
+        huber_gradient(output, target, T::constant(f64::from(self.delta)))
+    }
+}